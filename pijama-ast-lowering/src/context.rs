@@ -1,5 +1,5 @@
 use crate::{
-    error::{LowerErrorKind, LowerResult},
+    error::{LowerError, LowerErrorKind, LowerResult},
     lowering::Lower,
     scope::Scope,
 };
@@ -7,10 +7,30 @@ use crate::{
 use pijama_ast as ast;
 use pijama_hir as hir;
 use pijama_ty::{
+    base::BaseTy,
     inference::{Ty, TyContext},
-    ExprId,
+    AdtId, ExprId,
 };
-use pijama_utils::{index::IndexMap, span::Span};
+use pijama_utils::{
+    index::{Index, IndexMap},
+    span::Span,
+};
+
+use std::collections::{HashMap, HashSet};
+
+/// A definition in the type scope.
+enum TyDef<'source> {
+    /// A type that has already been lowered.
+    Lowered(Ty),
+    /// A record that has not been lowered yet.
+    Record(ast::StructDef<'source>),
+    /// A type alias that has not been lowered yet.
+    Alias(ast::AliasDef<'source>),
+    /// A record being lowered, used to detect records that contain themselves.
+    PendingRecord,
+    /// A type alias being lowered, used to detect aliases that refer to themselves.
+    PendingAlias,
+}
 
 /// The main structure to lower the AST.
 pub(crate) struct LowerContext<'source, 'tcx> {
@@ -24,6 +44,18 @@ pub(crate) struct LowerContext<'source, 'tcx> {
     pub(crate) global_scope: Scope<'source>,
     /// The type annotations for the locals of the current function.
     pub(crate) locals: IndexMap<hir::Local, Ty>,
+    /// The spans of the locals of the current function.
+    pub(crate) local_spans: IndexMap<hir::Local, Span>,
+    /// The spans of the expressions that have already been lowered.
+    spans: IndexMap<ExprId, Span>,
+    /// The type scope, with the definition of each type by name.
+    types: HashMap<&'source str, TyDef<'source>>,
+    /// The type parameters of the current function.
+    ///
+    /// These are found before any other type, so they shadow the types with the same name.
+    pub(crate) ty_params: Vec<(&'source str, Ty)>,
+    /// The type and position of each variant by the name of its constructor.
+    ctors: HashMap<&'source str, (AdtId, usize)>,
 }
 
 impl<'source, 'tcx> LowerContext<'source, 'tcx> {
@@ -33,16 +65,128 @@ impl<'source, 'tcx> LowerContext<'source, 'tcx> {
             tcx,
             functions: IndexMap::new(),
             locals: IndexMap::new(),
+            local_spans: IndexMap::new(),
+            spans: IndexMap::new(),
             scope: Scope::new(),
             global_scope: Scope::new(),
+            types: vec![
+                ("Bool", TyDef::Lowered(Ty::Base(BaseTy::Bool))),
+                ("Int", TyDef::Lowered(Ty::Base(BaseTy::Int))),
+            ]
+            .into_iter()
+            .collect(),
+            ty_params: Vec::new(),
+            ctors: HashMap::new(),
         }
     }
 
     /// Lower the AST representation of a program and consume the context in the process.
+    ///
+    /// Each function is lowered independently so all the errors in the program are reported.
     pub(crate) fn lower_program(
         mut self,
         mut program: ast::Program<'source>,
-    ) -> LowerResult<'source, hir::Program> {
+    ) -> Result<hir::Program, Vec<LowerError<'source>>> {
+        let mut errors = Vec::new();
+
+        // An algebraic data type with a single variant without fields named after another type
+        // is an alias of that type, because both are written in the same way.
+        let names = self
+            .types
+            .keys()
+            .copied()
+            .chain(program.adts.iter().map(|adt| adt.ident.symbol))
+            .chain(program.structs.iter().map(|def| def.ident.symbol))
+            .chain(program.aliases.iter().map(|def| def.ident.symbol))
+            .collect::<HashSet<_>>();
+
+        let (aliases, adts) = std::mem::take(&mut program.adts)
+            .into_iter()
+            .partition::<Vec<_>, _>(|adt| match adt.variants.as_slice() {
+                [variant] => {
+                    variant.fields.is_empty()
+                        && variant.ident.symbol != adt.ident.symbol
+                        && names.contains(variant.ident.symbol)
+                }
+                _ => false,
+            });
+        program.adts = adts;
+
+        for mut adt in aliases {
+            let ident = adt.variants.pop().unwrap().ident;
+            program.aliases.push(ast::AliasDef {
+                docs: adt.docs,
+                ident: adt.ident,
+                ty: ast::Ty {
+                    span: ident.span,
+                    kind: ast::TyKind::Base(ident),
+                },
+                span: adt.span,
+            });
+        }
+
+        // Assign an `AdtId` to each algebraic data type and register its constructors before
+        // lowering anything, so types and constructors can be used before their definition.
+        for (id, adt) in program.adts.iter().enumerate() {
+            let ty = Ty::Adt {
+                id: AdtId::new(id),
+                name: adt.ident.symbol.to_owned(),
+            };
+
+            if let Err(error) = self.define_ty(&adt.ident, TyDef::Lowered(ty)) {
+                errors.push(error);
+            }
+
+            for (index, variant) in adt.variants.iter().enumerate() {
+                if self
+                    .ctors
+                    .insert(variant.ident.symbol, (AdtId::new(id), index))
+                    .is_some()
+                {
+                    errors.push(
+                        LowerErrorKind::Redefined(variant.ident.symbol)
+                            .into_err(variant.ident.span),
+                    );
+                }
+            }
+        }
+
+        // Register the records and type aliases by name. They are lowered in order of
+        // appearance, but each one of them can be lowered earlier if it is used by another one.
+        let defs = std::mem::take(&mut program.structs)
+            .into_iter()
+            .map(|def| (def.ident.clone(), TyDef::Record(def)))
+            .chain(
+                std::mem::take(&mut program.aliases)
+                    .into_iter()
+                    .map(|def| (def.ident.clone(), TyDef::Alias(def))),
+            )
+            .collect::<Vec<_>>();
+
+        let mut idents = Vec::with_capacity(defs.len());
+        for (ident, def) in defs {
+            match self.define_ty(&ident, def) {
+                Ok(()) => idents.push(ident),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        for ident in idents {
+            if let Err(error) = self.find_ty(&ident) {
+                errors.push(error);
+            }
+        }
+
+        let mut adts = IndexMap::new();
+        for adt in std::mem::take(&mut program.adts) {
+            match self.lower(adt) {
+                Ok(adt) => {
+                    adts.insert(adt);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+
         // Find the position of the main function. Error if there is no main function.
         let (main_pos, main_ident) = program
             .functions
@@ -55,7 +199,7 @@ impl<'source, 'tcx> LowerContext<'source, 'tcx> {
                     None
                 }
             })
-            .ok_or_else(|| LowerErrorKind::MainNotFound.into_err(Span::dummy()))?;
+            .ok_or_else(|| vec![LowerErrorKind::MainNotFound.into_err(Span::dummy())])?;
 
         // Assign the first `FuncId` to the main function.
         let main_id = self.functions.insert(None);
@@ -65,6 +209,11 @@ impl<'source, 'tcx> LowerContext<'source, 'tcx> {
         // Remove the main function from the program so we don't assign another `FuncId` to it.
         let main_func = program.functions.remove(main_pos);
 
+        // Nobody calls the main function, so its type parameters could not be instantiated.
+        if let Some(ident) = main_func.ty_params.first() {
+            errors.push(LowerErrorKind::GenericMain.into_err(ident.span));
+        }
+
         let mut func_ids = Vec::with_capacity(program.functions.len());
         // Assign `FuncId`s to the remaining functions.
         for function in &program.functions {
@@ -76,23 +225,36 @@ impl<'source, 'tcx> LowerContext<'source, 'tcx> {
             func_ids.push(func_id);
         }
 
-        // Lower the main function.
-        *self
-            .functions
-            .get_mut(main_id)
-            .expect("Functions should be in scope before lowering them.") =
-            Some(self.lower(main_func)?);
-
-        // Lower the remaining functions.
-        for (func_id, function) in func_ids.into_iter().zip(program.functions.into_iter()) {
-            *self
-                .functions
-                .get_mut(func_id)
-                .expect("Functions should be in scope before lowering them.") =
-                Some(self.lower(function)?);
+        // Lower all the functions, starting with the main function.
+        let functions = std::iter::once((main_id, main_func))
+            .chain(func_ids.into_iter().zip(program.functions));
+
+        for (func_id, function) in functions {
+            match self.lower(function) {
+                Ok(function) => {
+                    *self
+                        .functions
+                        .get_mut(func_id)
+                        .expect("Functions should be in scope before lowering them.") =
+                        Some(function);
+                }
+                Err(error) => {
+                    // The scope of the function must be cleaned before lowering the next one.
+                    self.scope = Scope::new();
+                    self.ty_params = Vec::new();
+                    self.locals = IndexMap::new();
+                    self.local_spans = IndexMap::new();
+
+                    errors.push(error);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
         }
 
-        // Be sure that all functions have been lowererqleqaxad.
+        // Be sure that all functions have been lowered.
         let functions = self
             .functions
             .into_raw()
@@ -103,11 +265,128 @@ impl<'source, 'tcx> LowerContext<'source, 'tcx> {
         // Return a HIR program.
         Ok(hir::Program {
             functions: IndexMap::from_raw(functions),
+            adts,
+            spans: self.spans,
         })
     }
 
-    pub(crate) fn new_id(&self) -> ExprId {
-        self.tcx.new_expr_id()
+    /// Get a new `ExprId` for an expression and record its span.
+    pub(crate) fn new_id(&mut self, span: Span) -> ExprId {
+        let id = self.tcx.new_expr_id();
+        // Be sure that the spans are stored in the same order as the IDs were generated.
+        assert_eq!(
+            id,
+            self.spans.insert(span),
+            "Expression IDs are unorganized."
+        );
+        id
+    }
+
+    /// Insert a new local for the current function with its type and span.
+    pub(crate) fn insert_local(&mut self, ty: Ty, span: Span) -> hir::Local {
+        let local = self.locals.insert(ty);
+        self.local_spans.insert(span);
+        local
+    }
+
+    /// Add a type to the type scope.
+    ///
+    /// Types cannot be shadowed, so defining a type with the name of another one is an error.
+    fn define_ty(
+        &mut self,
+        ident: &ast::Ident<'source>,
+        def: TyDef<'source>,
+    ) -> LowerResult<'source, ()> {
+        if self.types.contains_key(ident.symbol) {
+            return Err(LowerErrorKind::Redefined(ident.symbol).into_err(ident.span));
+        }
+
+        self.types.insert(ident.symbol, def);
+
+        Ok(())
+    }
+
+    /// Find a type by its name.
+    ///
+    /// Records and type aliases are lowered the first time they are found. The type of a record
+    /// that contains itself would be infinite and an alias that refers to itself cannot be
+    /// expanded, so both are reported as errors.
+    pub(crate) fn find_ty(
+        &mut self,
+        ident: &ast::Ident<'source>,
+    ) -> LowerResult<'source, Option<Ty>> {
+        if let Some((_, ty)) = self
+            .ty_params
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == ident.symbol)
+        {
+            return Ok(Some(ty.clone()));
+        }
+
+        let def = match self.types.get_mut(ident.symbol) {
+            None => return Ok(None),
+            Some(TyDef::Lowered(ty)) => return Ok(Some(ty.clone())),
+            Some(TyDef::PendingRecord) => {
+                return Err(LowerErrorKind::RecursiveRecord(ident.symbol).into_err(ident.span))
+            }
+            Some(TyDef::PendingAlias) => {
+                return Err(LowerErrorKind::CyclicAlias(ident.symbol).into_err(ident.span))
+            }
+            Some(def @ TyDef::Record(_)) => std::mem::replace(def, TyDef::PendingRecord),
+            Some(def @ TyDef::Alias(_)) => std::mem::replace(def, TyDef::PendingAlias),
+        };
+
+        // The definition is global, so the type parameters of the current function are not in
+        // scope while lowering it.
+        let ty_params = std::mem::take(&mut self.ty_params);
+
+        let ty = match def {
+            TyDef::Record(def) => self
+                .lower_record_fields(def.fields)
+                .map(|fields| Ty::Record {
+                    name: ident.symbol.to_owned(),
+                    fields,
+                }),
+            TyDef::Alias(def) => self.lower(def.ty).map(|ty| Ty::Alias {
+                name: ident.symbol.to_owned(),
+                ty: Box::new(ty),
+            }),
+            _ => unreachable!("Only records and aliases are lowered when they are found."),
+        };
+
+        self.ty_params = ty_params;
+
+        // Use the error type if the definition has errors so they are reported only once.
+        let lowered = ty.as_ref().map_or(Ty::Error, Ty::clone);
+        self.types.insert(ident.symbol, TyDef::Lowered(lowered));
+
+        ty.map(Some)
+    }
+
+    /// Lower the fields of a record's definition.
+    fn lower_record_fields(
+        &mut self,
+        fields: Vec<(ast::Ident<'source>, ast::Ty<'source>)>,
+    ) -> LowerResult<'source, Vec<(String, Ty)>> {
+        let mut lowered: Vec<(String, Ty)> = Vec::with_capacity(fields.len());
+
+        for (ident, ty) in fields {
+            if lowered.iter().any(|(name, _)| name == ident.symbol) {
+                return Err(LowerErrorKind::Redefined(ident.symbol).into_err(ident.span));
+            }
+
+            lowered.push((ident.symbol.to_owned(), self.lower(ty)?));
+        }
+
+        Ok(lowered)
+    }
+
+    /// Find the type and position of a variant by the name of its constructor.
+    ///
+    /// Constructors cannot be shadowed, so they are found before any other identifier.
+    pub(crate) fn find_ctor(&self, ident: &ast::Ident<'source>) -> Option<(AdtId, usize)> {
+        self.ctors.get(ident.symbol).copied()
     }
 
     /// Lower a term that implements the [Lower] trait.
@@ -1,5 +1,7 @@
 use pijama_utils::{span::Span, spanned_type};
 
+use std::fmt;
+
 pub type LowerResult<'source, T> = Result<T, LowerError<'source>>;
 
 spanned_type!(pub LowerError<'source>, LowerErrorKind);
@@ -12,8 +14,81 @@ spanned_type!(pub LowerError<'source>, LowerErrorKind);
 pub enum LowerErrorKind<'source> {
     /// An identifier was used without being bound.
     UnboundIdent(&'source str),
+    /// A type, constructor or field was defined more than once.
+    Redefined(&'source str),
+    /// A record contains itself.
+    RecursiveRecord(&'source str),
+    /// A type alias refers to itself.
+    CyclicAlias(&'source str),
+    /// A type that is not a record was used to build a record.
+    NotARecord(&'source str),
+    /// A record does not have a field with this name.
+    UnknownField {
+        /// The name of the record.
+        record: String,
+        /// The name of the field.
+        field: &'source str,
+    },
+    /// A field of a record was not given a value.
+    MissingField {
+        /// The name of the record.
+        record: String,
+        /// The name of the field.
+        field: String,
+    },
+    /// A field was given more than one value.
+    DuplicateField(&'source str),
+    /// Type arguments were given to something that is not a function.
+    NotAFunction(&'source str),
     /// The current program does not have a `main` function.
     MainNotFound,
+    /// The `main` function has type parameters.
+    GenericMain,
+    /// A `let` binding has a pattern that might not match its value.
+    RefutablePattern,
+    /// An expression could not be parsed.
+    ///
+    /// This error is already reported by the parser and it is used to skip the functions with
+    /// syntax errors.
+    SyntaxError,
+}
+
+impl<'source> fmt::Display for LowerErrorKind<'source> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnboundIdent(symbol) => write!(f, "cannot find `{}` in this scope", symbol),
+            Self::Redefined(symbol) => {
+                write!(f, "the name `{}` is defined multiple times", symbol)
+            }
+            Self::RecursiveRecord(symbol) => {
+                write!(f, "recursive type `{}` has infinite size", symbol)
+            }
+            Self::CyclicAlias(symbol) => {
+                write!(f, "cycle detected when expanding type alias `{}`", symbol)
+            }
+            Self::NotARecord(symbol) => write!(f, "expected struct, found type `{}`", symbol),
+            Self::UnknownField { record, field } => {
+                write!(f, "struct `{}` has no field named `{}`", record, field)
+            }
+            Self::MissingField { record, field } => {
+                write!(
+                    f,
+                    "missing field `{}` in initializer of `{}`",
+                    field, record
+                )
+            }
+            Self::DuplicateField(symbol) => {
+                write!(f, "field `{}` specified more than once", symbol)
+            }
+            Self::NotAFunction(symbol) => {
+                write!(f, "expected function, found local `{}`", symbol)
+            }
+            Self::MainNotFound => write!(f, "the program does not have a `main` function"),
+            Self::GenericMain => write!(f, "the `main` function cannot have type parameters"),
+            Self::RefutablePattern => write!(f, "refutable pattern in local binding"),
+            Self::SyntaxError => write!(f, "the expression could not be parsed"),
+        }
+    }
 }
 
 impl<'source> LowerErrorKind<'source> {
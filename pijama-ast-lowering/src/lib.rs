@@ -4,7 +4,7 @@ mod lowering;
 mod scope;
 
 use context::LowerContext;
-use error::LowerResult;
+use error::LowerError;
 
 use pijama_ast as ast;
 use pijama_hir as hir;
@@ -13,10 +13,13 @@ use pijama_ty::inference::TyContext;
 /// Lower the AST representation of a program into the HIR.
 ///
 /// This method consumes the AST and requires a reference to the [TyContext] to introduce inference
-/// variables for the unknown types.
+/// variables for the unknown types. If lowering fails, all the errors found are returned.
+///
+/// Functions with syntax errors are skipped and a [error::LowerErrorKind::SyntaxError] is returned
+/// for each one of them.
 pub fn lower_ast<'source>(
     tcx: &TyContext,
     program: ast::Program<'source>,
-) -> LowerResult<'source, hir::Program> {
+) -> Result<hir::Program, Vec<LowerError<'source>>> {
     LowerContext::new(tcx).lower_program(program)
 }
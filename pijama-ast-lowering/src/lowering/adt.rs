@@ -0,0 +1,29 @@
+use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
+
+use pijama_ast as ast;
+use pijama_hir as hir;
+
+impl<'source, 'tcx> Lower<'source, 'tcx> for ast::AdtDef<'source> {
+    type Output = hir::AdtDef;
+
+    fn lower_with(
+        self,
+        lcx: &mut LowerContext<'source, 'tcx>,
+    ) -> LowerResult<'source, Self::Output> {
+        let variants = self
+            .variants
+            .into_iter()
+            .map(|variant| {
+                Ok(hir::Variant {
+                    name: variant.ident.symbol.to_owned(),
+                    fields: lcx.lower(variant.fields)?,
+                })
+            })
+            .collect::<LowerResult<Vec<_>>>()?;
+
+        Ok(hir::AdtDef {
+            name: self.ident.symbol.to_owned(),
+            variants,
+        })
+    }
+}
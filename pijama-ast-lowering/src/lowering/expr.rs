@@ -1,7 +1,13 @@
-use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
+use crate::{
+    context::LowerContext,
+    error::{LowerErrorKind, LowerResult},
+    lowering::Lower,
+};
 
 use pijama_ast as ast;
 use pijama_hir as hir;
+use pijama_ty::inference::Ty;
+use pijama_utils::span::Span;
 
 impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Expr<'source> {
     type Output = hir::Expr;
@@ -10,17 +16,36 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Expr<'source> {
         self,
         lcx: &mut LowerContext<'source, 'tcx>,
     ) -> LowerResult<'source, Self::Output> {
-        let id = lcx.new_id();
+        let id = lcx.new_id(self.span);
 
         let kind = match self.kind {
-            // Lowering an atom is straightforward.
-            ast::ExprKind::Atom(atom) => hir::ExprKind::Atom(lcx.lower(atom)?),
+            ast::ExprKind::Atom(atom) => {
+                // Constructors without fields are used like identifiers.
+                let ctor = match &atom {
+                    ast::Atom::Ident(ident) => lcx.find_ctor(ident),
+                    ast::Atom::Literal(_) => None,
+                };
+
+                match ctor {
+                    Some((adt, variant)) => hir::ExprKind::Construct {
+                        adt,
+                        variant,
+                        args: Vec::new(),
+                    },
+                    // Lowering an atom is straightforward.
+                    None => hir::ExprKind::Atom(lcx.lower(atom)?),
+                }
+            }
             ast::ExprKind::Let {
-                lhs,
+                lhs:
+                    ast::Pattern {
+                        kind: ast::PatternKind::Binding(lhs),
+                        ..
+                    },
                 lhs_ty,
                 rhs,
                 body,
-            } => {
+            } if lcx.find_ctor(&lhs).is_none() => {
                 // First, lower the right-hand side of the binding (the left-hand side should not
                 // be in scope yet!)
                 let rhs = lcx.lower(rhs)?;
@@ -28,7 +53,7 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Expr<'source> {
                 // Now lower the left-hand side type in order to insert it into the `locals` field
                 // and get a `Local` for the left-hand side.
                 let lhs_ty = lcx.lower(lhs_ty)?;
-                let lhs_local = lcx.locals.insert(lhs_ty);
+                let lhs_local = lcx.insert_local(lhs_ty, lhs.span);
 
                 // Push the left-hand side local onto the scope.
                 lcx.scope.push_ident(lhs, hir::Name::Local(lhs_local));
@@ -43,10 +68,82 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Expr<'source> {
                     body,
                 }
             }
-            ast::ExprKind::Call { func, args } => hir::ExprKind::Call {
-                func: lcx.lower(func)?,
-                args: lcx.lower(args)?,
-            },
+            // Destructuring bindings are lowered as a binding for the whole value followed by a
+            // binding for each projection of it.
+            ast::ExprKind::Let {
+                lhs,
+                lhs_ty,
+                rhs,
+                body,
+            } => {
+                let mut rhs = lcx.lower(rhs)?;
+
+                // If the pattern has a type, the right-hand side must have that type.
+                if let Some(lhs_ty) = lhs_ty {
+                    let span = lhs_ty.span;
+                    let lhs_ty = lcx.lower(lhs_ty)?;
+                    let local = lcx.insert_local(lhs_ty, span);
+
+                    rhs = Box::new(hir::Expr {
+                        id: lcx.new_id(self.span),
+                        kind: hir::ExprKind::Let {
+                            lhs: local,
+                            rhs,
+                            body: Box::new(hir::Expr {
+                                id: lcx.new_id(span),
+                                kind: hir::ExprKind::Atom(hir::Atom::Name(hir::Name::Local(local))),
+                            }),
+                        },
+                    });
+                }
+
+                let mut idents = Vec::new();
+                let mut binds = Vec::new();
+                let local = lower_pattern(lhs, lcx, &mut idents, &mut binds)?;
+
+                // Lower the body with all the identifiers of the pattern in scope.
+                let len = idents.len();
+                for (ident, local) in idents {
+                    lcx.scope.push_ident(ident, hir::Name::Local(local));
+                }
+                let mut body = lcx.lower(body)?;
+                for _ in 0..len {
+                    lcx.scope.pop_ident();
+                }
+
+                // Bind each projection, starting with the innermost binding.
+                for (lhs, rhs) in binds.into_iter().rev() {
+                    body = Box::new(hir::Expr {
+                        id: lcx.new_id(self.span),
+                        kind: hir::ExprKind::Let { lhs, rhs, body },
+                    });
+                }
+
+                hir::ExprKind::Let {
+                    lhs: local,
+                    rhs,
+                    body,
+                }
+            }
+            ast::ExprKind::Call { func, args } => {
+                let ctor = match &func.kind {
+                    ast::ExprKind::Atom(ast::Atom::Ident(ident)) => lcx.find_ctor(ident),
+                    _ => None,
+                };
+
+                match ctor {
+                    // Calling a constructor builds a value with it.
+                    Some((adt, variant)) => hir::ExprKind::Construct {
+                        adt,
+                        variant,
+                        args: lcx.lower(args)?,
+                    },
+                    None => hir::ExprKind::Call {
+                        func: lcx.lower(func)?,
+                        args: lcx.lower(args)?,
+                    },
+                }
+            }
             ast::ExprKind::UnaryOp(un_op, op) => {
                 let un_op = match un_op.kind {
                     ast::UnOpKind::Not => hir::UnOp::Not,
@@ -94,8 +191,293 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Expr<'source> {
             ast::ExprKind::Tuple { fields } => hir::ExprKind::Tuple {
                 fields: lcx.lower(fields)?,
             },
+            ast::ExprKind::Projection { tuple, index } => hir::ExprKind::Projection {
+                tuple: lcx.lower(tuple)?,
+                index,
+            },
+            ast::ExprKind::Record { ident, fields } => {
+                let ty = lcx.find_ty(&ident)?.ok_or_else(|| {
+                    LowerErrorKind::UnboundIdent(ident.symbol).into_err(ident.span)
+                })?;
+
+                // The type can be an alias of a record.
+                if !matches!(ty.unaliased(), Ty::Record { .. } | Ty::Error) {
+                    return Err(LowerErrorKind::NotARecord(ident.symbol).into_err(ident.span));
+                }
+
+                let fields = lower_record_fields(&ty, fields, self.span, lcx)?;
+
+                hir::ExprKind::Record { ty, fields }
+            }
+            ast::ExprKind::Field { record, field } => hir::ExprKind::Field {
+                record: lcx.lower(record)?,
+                name: field.symbol.to_owned(),
+            },
+            ast::ExprKind::Update { record, fields } => {
+                let record = lcx.lower(record)?;
+
+                let mut lowered: Vec<(String, hir::Expr)> = Vec::with_capacity(fields.len());
+                for (ident, expr) in fields {
+                    if lowered.iter().any(|(name, _)| name == ident.symbol) {
+                        return Err(
+                            LowerErrorKind::DuplicateField(ident.symbol).into_err(ident.span)
+                        );
+                    }
+
+                    lowered.push((ident.symbol.to_owned(), lcx.lower(expr)?));
+                }
+
+                hir::ExprKind::Update {
+                    record,
+                    fields: lowered,
+                }
+            }
+            ast::ExprKind::Match { scrutinee, arms } => {
+                let scrutinee = lcx.lower(scrutinee)?;
+
+                let arms = arms
+                    .into_iter()
+                    .map(|arm| {
+                        let mut idents = Vec::new();
+                        let pattern = lower_match_pattern(arm.pattern, lcx, &mut idents)?;
+
+                        // Lower the body with all the identifiers of the pattern in scope.
+                        let len = idents.len();
+                        for (ident, local) in idents {
+                            lcx.scope.push_ident(ident, hir::Name::Local(local));
+                        }
+                        let body = lcx.lower(arm.body);
+                        for _ in 0..len {
+                            lcx.scope.pop_ident();
+                        }
+
+                        Ok(hir::Arm {
+                            pattern,
+                            body: body?,
+                        })
+                    })
+                    .collect::<LowerResult<Vec<_>>>()?;
+
+                hir::ExprKind::Match { scrutinee, arms }
+            }
+            ast::ExprKind::Lambda {
+                params,
+                return_ty,
+                body,
+            } => {
+                let arity = params.len();
+
+                // The parameters are locals of the current function.
+                let mut param_locals = Vec::with_capacity(arity);
+                for (param_ident, param_ty) in params {
+                    let param_ty = lcx.lower(param_ty)?;
+                    let param_local = lcx.insert_local(param_ty, param_ident.span);
+                    lcx.scope
+                        .push_ident(param_ident, hir::Name::Local(param_local));
+                    param_locals.push(param_local);
+                }
+
+                // Lower the body with all the parameters in scope. Any other local in scope can
+                // be used too.
+                let body = lcx.lower(body);
+                for _ in 0..arity {
+                    lcx.scope.pop_ident();
+                }
+
+                hir::ExprKind::Lambda {
+                    params: param_locals,
+                    return_ty: lcx.lower(return_ty)?,
+                    body: body?,
+                }
+            }
+            ast::ExprKind::Instantiate { func, ty_args } => {
+                let symbol = func.symbol;
+                let span = func.span;
+
+                // Only functions have type parameters.
+                match lcx.lower(func)? {
+                    hir::Name::FuncPtr(func) => hir::ExprKind::Instantiate {
+                        func,
+                        ty_args: lcx.lower(ty_args)?,
+                    },
+                    hir::Name::Local(_) => {
+                        return Err(LowerErrorKind::NotAFunction(symbol).into_err(span))
+                    }
+                }
+            }
+            // Expressions that could not be parsed cannot be lowered.
+            ast::ExprKind::Error => {
+                return Err(LowerErrorKind::SyntaxError.into_err(self.span));
+            }
         };
 
         Ok(hir::Expr { id, kind })
     }
 }
+
+/// Insert a local for the value matched by a pattern and return it.
+///
+/// The identifiers bound by the pattern and their locals are pushed into `idents`, and the
+/// bindings required to destructure the value are pushed into `binds` in the order they must be
+/// evaluated.
+///
+/// Patterns that might not match the value, like literals, are rejected.
+fn lower_pattern<'source, 'tcx>(
+    pattern: ast::Pattern<'source>,
+    lcx: &mut LowerContext<'source, 'tcx>,
+    idents: &mut Vec<(ast::Ident<'source>, hir::Local)>,
+    binds: &mut Vec<(hir::Local, Box<hir::Expr>)>,
+) -> LowerResult<'source, hir::Local> {
+    let local = match pattern.kind {
+        ast::PatternKind::Wildcard => lcx.insert_local(lcx.tcx.new_ty(), pattern.span),
+        ast::PatternKind::Binding(ident) if lcx.find_ctor(&ident).is_none() => {
+            let local = lcx.insert_local(lcx.tcx.new_ty(), ident.span);
+            idents.push((ident, local));
+            local
+        }
+        ast::PatternKind::Binding(_)
+        | ast::PatternKind::Literal(_)
+        | ast::PatternKind::Constructor { .. } => {
+            return Err(LowerErrorKind::RefutablePattern.into_err(pattern.span));
+        }
+        ast::PatternKind::Tuple { fields } => {
+            // The value must be a tuple with as many fields as the pattern.
+            let fields_ty = fields.iter().map(|_| lcx.tcx.new_ty()).collect();
+            let local = lcx.insert_local(Ty::Tuple { fields: fields_ty }, pattern.span);
+
+            for (index, field) in fields.into_iter().enumerate() {
+                let span = field.span;
+
+                let tuple = hir::Expr {
+                    id: lcx.new_id(span),
+                    kind: hir::ExprKind::Atom(hir::Atom::Name(hir::Name::Local(local))),
+                };
+                let projection = hir::Expr {
+                    id: lcx.new_id(span),
+                    kind: hir::ExprKind::Projection {
+                        tuple: Box::new(tuple),
+                        index,
+                    },
+                };
+
+                // The binding for the field must be evaluated before the bindings of its own
+                // fields.
+                let position = binds.len();
+                let field_local = lower_pattern(field, lcx, idents, binds)?;
+                binds.insert(position, (field_local, Box::new(projection)));
+            }
+
+            local
+        }
+    };
+
+    Ok(local)
+}
+
+/// Lower the pattern of a `match` arm.
+///
+/// The identifiers bound by the pattern and their locals are pushed into `idents`.
+fn lower_match_pattern<'source, 'tcx>(
+    pattern: ast::Pattern<'source>,
+    lcx: &mut LowerContext<'source, 'tcx>,
+    idents: &mut Vec<(ast::Ident<'source>, hir::Local)>,
+) -> LowerResult<'source, hir::Pattern> {
+    let kind = match pattern.kind {
+        ast::PatternKind::Wildcard => hir::PatternKind::Wildcard,
+        ast::PatternKind::Binding(ident) => match lcx.find_ctor(&ident) {
+            // Constructors without fields look like bindings.
+            Some((adt, variant)) => hir::PatternKind::Constructor {
+                adt,
+                variant,
+                fields: Vec::new(),
+            },
+            None => {
+                let local = lcx.insert_local(lcx.tcx.new_ty(), ident.span);
+                idents.push((ident, local));
+                hir::PatternKind::Binding(local)
+            }
+        },
+        ast::PatternKind::Literal(literal) => hir::PatternKind::Literal(lcx.lower(literal)?),
+        ast::PatternKind::Constructor { ident, fields } => {
+            let (adt, variant) = lcx
+                .find_ctor(&ident)
+                .ok_or_else(|| LowerErrorKind::UnboundIdent(ident.symbol).into_err(ident.span))?;
+
+            hir::PatternKind::Constructor {
+                adt,
+                variant,
+                fields: fields
+                    .into_iter()
+                    .map(|field| lower_match_pattern(field, lcx, idents))
+                    .collect::<LowerResult<Vec<_>>>()?,
+            }
+        }
+        ast::PatternKind::Tuple { fields } => hir::PatternKind::Tuple {
+            fields: fields
+                .into_iter()
+                .map(|field| lower_match_pattern(field, lcx, idents))
+                .collect::<LowerResult<Vec<_>>>()?,
+        },
+    };
+
+    Ok(hir::Pattern {
+        kind,
+        span: pattern.span,
+    })
+}
+
+/// Lower the fields of a record and find their positions in the record's definition.
+///
+/// Every field of the record must be given a value exactly once.
+fn lower_record_fields<'source, 'tcx>(
+    ty: &Ty,
+    fields: Vec<(ast::Ident<'source>, ast::Expr<'source>)>,
+    span: Span,
+    lcx: &mut LowerContext<'source, 'tcx>,
+) -> LowerResult<'source, Vec<(usize, hir::Expr)>> {
+    let (name, decl_fields) = match ty.unaliased() {
+        Ty::Record { name, fields } => (name, fields),
+        // The definition of the record has errors that were already reported, so the fields are
+        // lowered without checking them.
+        _ => {
+            return fields
+                .into_iter()
+                .enumerate()
+                .map(|(pos, (_, expr))| Ok((pos, lcx.lower(expr)?)))
+                .collect();
+        }
+    };
+
+    let mut lowered: Vec<(usize, hir::Expr)> = Vec::with_capacity(fields.len());
+
+    for (ident, expr) in fields {
+        let pos = decl_fields
+            .iter()
+            .position(|(field, _)| field == ident.symbol)
+            .ok_or_else(|| {
+                LowerErrorKind::UnknownField {
+                    record: name.clone(),
+                    field: ident.symbol,
+                }
+                .into_err(ident.span)
+            })?;
+
+        if lowered.iter().any(|(other, _)| *other == pos) {
+            return Err(LowerErrorKind::DuplicateField(ident.symbol).into_err(ident.span));
+        }
+
+        lowered.push((pos, lcx.lower(expr)?));
+    }
+
+    let missing = (0..decl_fields.len()).find(|pos| lowered.iter().all(|(other, _)| other != pos));
+
+    if let Some(pos) = missing {
+        return Err(LowerErrorKind::MissingField {
+            record: name.clone(),
+            field: decl_fields[pos].0.clone(),
+        }
+        .into_err(span));
+    }
+
+    Ok(lowered)
+}
@@ -1,7 +1,12 @@
-use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
+use crate::{
+    context::LowerContext,
+    error::{LowerErrorKind, LowerResult},
+    lowering::Lower,
+};
 
 use pijama_ast as ast;
 use pijama_hir as hir;
+use pijama_ty::inference::Ty;
 use pijama_utils::index::IndexMap;
 
 impl<'source, 'tcx> Lower<'source, 'tcx> for ast::FuncDef<'source> {
@@ -11,14 +16,32 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::FuncDef<'source> {
         self,
         lcx: &mut LowerContext<'source, 'tcx>,
     ) -> LowerResult<'source, Self::Output> {
+        // Keep the name of the function.
+        let name = self.ident.symbol.to_owned();
         // Compute the arity of the function.
         let arity = self.params.len();
 
+        // Push the type parameters into the type scope. Each one of them has a new type variable.
+        let mut ty_params = Vec::with_capacity(self.ty_params.len());
+        for ident in self.ty_params {
+            if lcx.ty_params.iter().any(|(name, _)| *name == ident.symbol) {
+                return Err(LowerErrorKind::Redefined(ident.symbol).into_err(ident.span));
+            }
+
+            let ty = Ty::Param {
+                var: lcx.tcx.new_var(),
+                name: ident.symbol.to_owned(),
+            };
+
+            lcx.ty_params.push((ident.symbol, ty.clone()));
+            ty_params.push(ty);
+        }
+
         for (param_ident, param_ty) in self.params {
             // Lower the type of each parameter and get a local for the parameter by inserting it
             // into the `locals` field.
             let param_ty = lcx.lower(param_ty)?;
-            let param_local = lcx.locals.insert(param_ty);
+            let param_local = lcx.insert_local(param_ty, param_ident.span);
             // Push the local into scope.
             lcx.scope
                 .push_ident(param_ident, hir::Name::Local(param_local));
@@ -34,15 +57,23 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::FuncDef<'source> {
 
         // Take the `locals` field and replace it with an empty map.
         let locals = std::mem::replace(&mut lcx.locals, IndexMap::new());
+        let local_spans = std::mem::replace(&mut lcx.local_spans, IndexMap::new());
 
         // Lower the return type.
         let return_ty = lcx.lower(self.return_ty)?;
 
+        // Remove all the type parameters from the type scope.
+        lcx.ty_params.clear();
+
         Ok(hir::Func {
+            name,
             arity,
+            ty_params,
             locals,
             return_ty,
             body,
+            local_spans,
+            span: self.span,
         })
     }
 }
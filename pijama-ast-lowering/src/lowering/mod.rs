@@ -1,3 +1,4 @@
+mod adt;
 mod atom;
 mod expr;
 mod func;
@@ -5,7 +5,7 @@ use crate::{
 };
 
 use pijama_ast as ast;
-use pijama_ty::{base::BaseTy, inference::Ty};
+use pijama_ty::inference::Ty;
 
 impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Ty<'source> {
     type Output = Ty;
@@ -15,13 +15,10 @@ impl<'source, 'tcx> Lower<'source, 'tcx> for ast::Ty<'source> {
         lcx: &mut LowerContext<'source, 'tcx>,
     ) -> LowerResult<'source, Self::Output> {
         match self.kind {
-            ast::TyKind::Base(ident) => match ident.symbol {
-                // Check that base types have the right symbol.
-                "Bool" => Ok(Ty::Base(BaseTy::Bool)),
-                "Int" => Ok(Ty::Base(BaseTy::Int)),
-                // Return an error if the symbol is not right.
-                symbol => Err(LowerErrorKind::UnboundIdent(symbol).into_err(ident.span)),
-            },
+            // The symbol must be the name of a type in scope.
+            ast::TyKind::Base(ident) => lcx
+                .find_ty(&ident)?
+                .ok_or_else(|| LowerErrorKind::UnboundIdent(ident.symbol).into_err(ident.span)),
             // Lower function types recursively.
             ast::TyKind::Func {
                 params_ty,
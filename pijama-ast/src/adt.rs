@@ -0,0 +1,26 @@
+use crate::{ident::Ident, ty::Ty};
+
+use pijama_utils::span::Span;
+
+/// The AST representation of an algebraic data type's definition, like
+/// `type Shape = Circle(Int) | Rect(Int, Int)`.
+#[derive(Debug)]
+pub struct AdtDef<'source> {
+    /// The lines of the documentation comments of the type, without the leading `///`.
+    pub docs: Vec<&'source str>,
+    /// The identifier of the type.
+    pub ident: Ident<'source>,
+    /// The variants of the type.
+    pub variants: Vec<Variant<'source>>,
+    /// The span of the definition.
+    pub span: Span,
+}
+
+/// The AST representation of a variant of an algebraic data type.
+#[derive(Debug)]
+pub struct Variant<'source> {
+    /// The identifier of the variant's constructor.
+    pub ident: Ident<'source>,
+    /// The types of the fields of the variant.
+    pub fields: Vec<Ty<'source>>,
+}
@@ -0,0 +1,19 @@
+use crate::{ident::Ident, ty::Ty};
+
+use pijama_utils::span::Span;
+
+/// The AST representation of a type alias, like `type Point = (Int, Int)`.
+///
+/// An alias whose type is a single identifier, like `type Flag = Bool`, is parsed as an
+/// algebraic data type with a single variant because both are written in the same way.
+#[derive(Debug)]
+pub struct AliasDef<'source> {
+    /// The lines of the documentation comments of the alias, without the leading `///`.
+    pub docs: Vec<&'source str>,
+    /// The identifier of the alias.
+    pub ident: Ident<'source>,
+    /// The type the alias stands for.
+    pub ty: Ty<'source>,
+    /// The span of the definition.
+    pub span: Span,
+}
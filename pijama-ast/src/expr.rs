@@ -2,6 +2,7 @@ use crate::{
     atom::Atom,
     ident::Ident,
     op::{BinOp, UnOp},
+    pattern::{Arm, Pattern},
     ty::Ty,
 };
 
@@ -16,18 +17,18 @@ pub enum ExprKind<'source> {
     Atom(Atom<'source>),
     /// A local binding.
     Let {
-        /// The identifier to be bound.
-        lhs: Ident<'source>,
-        /// The type of the identfiier.
+        /// The pattern to be bound.
+        lhs: Pattern<'source>,
+        /// The type of the pattern.
         lhs_ty: Option<Ty<'source>>,
-        /// The expression whose value will be bound to the ident.
+        /// The expression whose value will be bound to the pattern.
         rhs: Box<Expr<'source>>,
         /// The expression where this binding is valid.
         body: Box<Expr<'source>>,
     },
     Call {
-        /// The name of the called function.
-        func: Ident<'source>,
+        /// The called function.
+        func: Box<Expr<'source>>,
         /// The arguments of the call.
         args: Vec<Expr<'source>>,
     },
@@ -47,4 +48,59 @@ pub enum ExprKind<'source> {
     Tuple {
         fields: Vec<Expr<'source>>,
     },
+    /// A tuple projection, like `t.0`.
+    Projection {
+        /// The projected tuple.
+        tuple: Box<Expr<'source>>,
+        /// The index of the field.
+        index: usize,
+    },
+    /// A record, like `Point { x: 1, y: 2 }`.
+    Record {
+        /// The identifier of the record's type.
+        ident: Ident<'source>,
+        /// The identifier and value of each field, in the order they were written.
+        fields: Vec<(Ident<'source>, Expr<'source>)>,
+    },
+    /// A record field access, like `p.x`.
+    Field {
+        /// The accessed record.
+        record: Box<Expr<'source>>,
+        /// The identifier of the field.
+        field: Ident<'source>,
+    },
+    /// A functional record update, like `{ p with x = 3 }`.
+    ///
+    /// This is a copy of the record where some fields have new values.
+    Update {
+        /// The updated record.
+        record: Box<Expr<'source>>,
+        /// The identifier and new value of each updated field.
+        fields: Vec<(Ident<'source>, Expr<'source>)>,
+    },
+    /// A `match` expression.
+    Match {
+        /// The expression being matched.
+        scrutinee: Box<Expr<'source>>,
+        /// The arms of the expression, tested in order.
+        arms: Vec<Arm<'source>>,
+    },
+    /// An anonymous function, like `fn(x) do x + n end`.
+    Lambda {
+        /// The identifiers for the parameters of the function and their types.
+        params: Vec<(Ident<'source>, Option<Ty<'source>>)>,
+        /// The return type of the function.
+        return_ty: Option<Ty<'source>>,
+        /// The body of the function.
+        body: Box<Expr<'source>>,
+    },
+    /// A function with explicit type arguments, like `id[Int]`.
+    Instantiate {
+        /// The identifier of the function.
+        func: Ident<'source>,
+        /// The type arguments, in the same order as the type parameters of the function.
+        ty_args: Vec<Ty<'source>>,
+    },
+    /// A placeholder for an expression that could not be parsed.
+    Error,
 }
@@ -5,8 +5,12 @@ use pijama_utils::span::Span;
 #[derive(Debug)]
 /// The AST representation of a function's definition.
 pub struct FuncDef<'source> {
+    /// The lines of the documentation comments of the function, without the leading `///`.
+    pub docs: Vec<&'source str>,
     /// The identifier of the function.
     pub ident: Ident<'source>,
+    /// The identifiers for the type parameters of the function.
+    pub ty_params: Vec<Ident<'source>>,
     /// The identifiers for the parameters of the function and their types.
     pub params: Vec<(Ident<'source>, Option<Ty<'source>>)>,
     /// The return type of the function.
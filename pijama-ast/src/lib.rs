@@ -8,20 +8,28 @@
 //! so on.
 //! - Type annotations are optional, that's why every `Ty` term is wrapped in an `Option`.
 
+mod adt;
+mod alias;
 mod atom;
 mod expr;
 mod func;
 mod ident;
 mod literal;
 mod op;
+mod pattern;
 mod program;
+mod record;
 mod ty;
 
+pub use adt::{AdtDef, Variant};
+pub use alias::AliasDef;
 pub use atom::Atom;
 pub use expr::{Expr, ExprKind};
 pub use func::FuncDef;
 pub use ident::Ident;
 pub use literal::{Literal, LiteralKind};
 pub use op::{BinOp, BinOpKind, UnOp, UnOpKind};
-pub use program::Program;
+pub use pattern::{Arm, Pattern, PatternKind};
+pub use program::{Item, Program};
+pub use record::StructDef;
 pub use ty::{Ty, TyKind};
@@ -0,0 +1,39 @@
+use crate::{expr::Expr, ident::Ident, literal::Literal};
+
+use pijama_utils::spanned_type;
+
+spanned_type!(pub Pattern<'source>, PatternKind);
+
+/// The AST representation of a pattern.
+#[derive(Debug)]
+pub enum PatternKind<'source> {
+    /// A pattern that matches any value without binding it, written `_`.
+    Wildcard,
+    /// A pattern that binds a value to an identifier.
+    Binding(Ident<'source>),
+    /// A pattern that only matches a literal value.
+    Literal(Literal),
+    /// A pattern that destructures a variant of an algebraic data type.
+    ///
+    /// Constructors without fields are parsed as bindings because they look like identifiers.
+    Constructor {
+        /// The identifier of the variant's constructor.
+        ident: Ident<'source>,
+        /// The patterns for each field of the variant.
+        fields: Vec<Pattern<'source>>,
+    },
+    /// A pattern that destructures a tuple.
+    Tuple {
+        /// The patterns for each field of the tuple.
+        fields: Vec<Pattern<'source>>,
+    },
+}
+
+/// An arm of a `match` expression.
+#[derive(Debug)]
+pub struct Arm<'source> {
+    /// The pattern tested against the scrutinee.
+    pub pattern: Pattern<'source>,
+    /// The expression evaluated if the pattern matches.
+    pub body: Expr<'source>,
+}
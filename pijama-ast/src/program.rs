@@ -1,8 +1,27 @@
-use crate::func::FuncDef;
+use crate::{adt::AdtDef, alias::AliasDef, func::FuncDef, record::StructDef};
 
 /// The AST representation of a program.
 #[derive(Debug)]
 pub struct Program<'source> {
     /// The functions of the program.
     pub functions: Vec<FuncDef<'source>>,
+    /// The algebraic data types of the program.
+    pub adts: Vec<AdtDef<'source>>,
+    /// The records of the program.
+    pub structs: Vec<StructDef<'source>>,
+    /// The type aliases of the program.
+    pub aliases: Vec<AliasDef<'source>>,
+}
+
+/// The AST representation of a top-level item of a program.
+#[derive(Debug)]
+pub enum Item<'source> {
+    /// A function's definition.
+    Func(Box<FuncDef<'source>>),
+    /// An algebraic data type's definition.
+    Adt(AdtDef<'source>),
+    /// A record's definition.
+    Struct(StructDef<'source>),
+    /// A type alias' definition.
+    Alias(AliasDef<'source>),
 }
@@ -0,0 +1,16 @@
+use crate::{ident::Ident, ty::Ty};
+
+use pijama_utils::span::Span;
+
+/// The AST representation of a record's definition, like `struct Point { x: Int, y: Int }`.
+#[derive(Debug)]
+pub struct StructDef<'source> {
+    /// The lines of the documentation comments of the record, without the leading `///`.
+    pub docs: Vec<&'source str>,
+    /// The identifier of the record.
+    pub ident: Ident<'source>,
+    /// The identifier and type of each field of the record.
+    pub fields: Vec<(Ident<'source>, Ty<'source>)>,
+    /// The span of the definition.
+    pub span: Span,
+}
@@ -1,25 +1,71 @@
-use crate::{error::LowerResult, lowering::Lower};
+use crate::{
+    error::LowerResult,
+    lowering::{closure::Closure, Lower},
+};
 
+use pijama_hir as hir;
 use pijama_mir as mir;
-use pijama_ty::{ty::Ty, ExprId};
-use pijama_tycheck::{Table, Unifier};
-use pijama_utils::index::IndexMap;
+use pijama_ty::{inference, inference::TyVar, ty::Ty, AdtId, ExprId};
+use pijama_tycheck::{Table, TableBuilder, Unifier};
+use pijama_utils::{
+    index::{Index, IndexMap},
+    span::Span,
+};
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 /// The main structure to lower the HIR.
+///
+/// Polymorphic functions are monomorphized during lowering: each function is lowered once for
+/// each combination of types used to instantiate its quantified variables. Lambdas are lifted into
+/// closures that are lowered once for each instance of the function where they are defined.
 pub(crate) struct LowerContext {
     /// The unifier used to instantiate types.
     pub(crate) unifier: Unifier,
+    /// The types of the HIR expressions.
+    types: TableBuilder,
+    /// The types of the MIR expressions.
     pub(crate) table: Table,
+    /// The types of the HIR locals of the function being lowered.
+    hir_locals: IndexMap<hir::Local, inference::Ty>,
+    /// The MIR local of each HIR local used so far by the function being lowered.
+    locals: HashMap<hir::Local, mir::Local>,
     local_types: IndexMap<mir::Local, Ty>,
+    /// The types used to instantiate the quantified variables of the function being lowered.
+    args: BTreeMap<TyVar, Ty>,
+    /// The `FuncId` of each instance of a function.
+    instances: HashMap<(hir::FuncId, Vec<Ty>), mir::FuncId>,
+    /// The number of functions scheduled to be lowered so far, used to assign their IDs.
+    scheduled: usize,
+    /// The functions that have not been lowered yet, with the types for the quantified variables
+    /// of the function they come from.
+    pending: VecDeque<(PendingFunc, BTreeMap<TyVar, Ty>, mir::FuncId)>,
+    /// The spans of the HIR expressions.
+    spans: IndexMap<ExprId, Span>,
+    /// The lowered algebraic data types of the program.
+    pub(crate) adts: IndexMap<AdtId, mir::AdtDef>,
 }
 
 impl LowerContext {
     /// Create a new lowering context.
-    pub(crate) fn new(unifier: Unifier, table: Table) -> Self {
+    pub(crate) fn new(
+        unifier: Unifier,
+        types: TableBuilder,
+        spans: IndexMap<ExprId, Span>,
+    ) -> Self {
         Self {
             unifier,
-            table,
+            types,
+            table: Table::new(),
+            hir_locals: IndexMap::new(),
+            locals: HashMap::new(),
             local_types: IndexMap::new(),
+            args: BTreeMap::new(),
+            instances: HashMap::new(),
+            scheduled: 0,
+            pending: VecDeque::new(),
+            spans,
+            adts: IndexMap::new(),
         }
     }
 
@@ -28,10 +74,76 @@ impl LowerContext {
         term.lower_with(self)
     }
 
+    /// Get the type of a MIR expression.
     pub(crate) fn get_expr_ty(&self, id: ExprId) -> Option<&Ty> {
         self.table.get_ty(id)
     }
 
+    /// Get the type of a HIR expression for the instance being lowered.
+    pub(crate) fn get_hir_expr_ty(&self, id: ExprId) -> Option<Ty> {
+        let ty = self.types.get_ty(id)?.clone();
+        Some(self.instantiate(ty))
+    }
+
+    /// Get the span of a HIR expression.
+    pub(crate) fn get_span(&self, id: ExprId) -> Span {
+        *self
+            .spans
+            .get(id)
+            .expect("Every expression should have a span.")
+    }
+
+    /// Instantiate a type for the instance being lowered.
+    pub(crate) fn instantiate(&self, ty: inference::Ty) -> Ty {
+        self.unifier.instantiate_with(ty, &self.args)
+    }
+
+    /// Get a variant of an algebraic data type.
+    pub(crate) fn get_variant(&self, adt: AdtId, variant: usize) -> &mir::Variant {
+        &self
+            .adts
+            .get(adt)
+            .expect("Every algebraic data type should be lowered before its uses.")
+            .variants[variant]
+    }
+
+    /// Start lowering a function whose HIR locals have the given types.
+    pub(crate) fn enter_func(&mut self, hir_locals: IndexMap<hir::Local, inference::Ty>) {
+        self.hir_locals = hir_locals;
+        self.locals.clear();
+    }
+
+    /// Get the HIR locals of the function being lowered.
+    pub(crate) fn hir_locals(&self) -> &IndexMap<hir::Local, inference::Ty> {
+        &self.hir_locals
+    }
+
+    /// Get the type of a HIR local of the function being lowered for the instance being lowered.
+    pub(crate) fn hir_local_ty(&self, local: hir::Local) -> Ty {
+        let ty = self
+            .hir_locals
+            .get(local)
+            .expect("Every local should have a type.")
+            .clone();
+
+        self.instantiate(ty)
+    }
+
+    /// Get the MIR local for a HIR local of the function being lowered.
+    ///
+    /// A new MIR local is created the first time each HIR local is used, so the locals of a
+    /// function are numbered in order of appearance.
+    pub(crate) fn get_local(&mut self, local: hir::Local) -> mir::Local {
+        if let Some(&mir_local) = self.locals.get(&local) {
+            return mir_local;
+        }
+
+        let mir_local = self.store_local_ty(self.hir_local_ty(local));
+        self.locals.insert(local, mir_local);
+
+        mir_local
+    }
+
     pub(crate) fn store_local_ty(&mut self, ty: Ty) -> mir::Local {
         self.local_types.insert(ty)
     }
@@ -39,4 +151,76 @@ impl LowerContext {
     pub(crate) fn get_local_types(&mut self) -> IndexMap<mir::Local, Ty> {
         std::mem::replace(&mut self.local_types, IndexMap::new())
     }
+
+    /// Get the `FuncId` of the instance of a function with the given types for its quantified
+    /// variables.
+    ///
+    /// If the instance does not exist yet, it is scheduled to be lowered.
+    pub(crate) fn func_instance(&mut self, func_id: hir::FuncId, args: Vec<Ty>) -> mir::FuncId {
+        let key = (func_id, args);
+
+        if let Some(&instance_id) = self.instances.get(&key) {
+            return instance_id;
+        }
+
+        let instance_id = self.new_func_id();
+        let vars = &self.unifier.scheme(key.0).vars;
+        let args = vars.iter().copied().zip(key.1.iter().cloned()).collect();
+        self.pending
+            .push_back((PendingFunc::Instance(key.0), args, instance_id));
+        self.instances.insert(key, instance_id);
+
+        instance_id
+    }
+
+    /// Get the `FuncId` of a closure lifted from a lambda of the function being lowered and
+    /// schedule it to be lowered.
+    ///
+    /// The closure is lowered using the same types for the quantified variables as the current
+    /// instance.
+    pub(crate) fn closure_instance(&mut self, closure: Closure) -> mir::FuncId {
+        let closure_id = self.new_func_id();
+        self.pending
+            .push_back((PendingFunc::Closure(closure), self.args.clone(), closure_id));
+
+        closure_id
+    }
+
+    /// Get a new `FuncId` for a function.
+    fn new_func_id(&mut self) -> mir::FuncId {
+        let func_id = mir::FuncId::new(self.scheduled);
+        self.scheduled += 1;
+        func_id
+    }
+
+    /// Get the `FuncId` of the instance of a function used by a HIR expression.
+    pub(crate) fn func_instance_at(&mut self, func_id: hir::FuncId, id: ExprId) -> mir::FuncId {
+        // Find the concrete types of the quantified variables of the function at this expression.
+        let args = self
+            .unifier
+            .instance_args(id)
+            .expect("Every use of a function should have types for its quantified variables.")
+            .iter()
+            .map(|ty| self.instantiate(ty.clone()))
+            .collect();
+
+        self.func_instance(func_id, args)
+    }
+
+    /// Take the next function to be lowered and use its types for the quantified variables of
+    /// the function it comes from.
+    pub(crate) fn next_func(&mut self) -> Option<(PendingFunc, mir::FuncId)> {
+        let (func, args, func_id) = self.pending.pop_front()?;
+        self.args = args;
+
+        Some((func, func_id))
+    }
+}
+
+/// A function that has been scheduled to be lowered.
+pub(crate) enum PendingFunc {
+    /// An instance of a function of the HIR.
+    Instance(hir::FuncId),
+    /// A closure lifted from a lambda.
+    Closure(Closure),
 }
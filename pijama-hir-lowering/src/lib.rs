@@ -7,17 +7,23 @@ use error::LowerResult;
 
 use pijama_hir as hir;
 use pijama_mir as mir;
-use pijama_tycheck::{Table, Unifier};
+use pijama_tycheck::{Table, TableBuilder, Unifier};
+use pijama_utils::index::IndexMap;
 
 /// Lower the HIR of a program into the mir representation.
 ///
-/// This method consumes the HIR and requires an [Unifier] to instantiate inference variables.
+/// This method consumes the HIR and requires an [Unifier] and the types of the HIR expressions to
+/// instantiate inference variables. Polymorphic functions are monomorphized, lambdas are lifted
+/// into closures and only the functions reachable from the main function are lowered. The
+/// returned [Table] has the types of the MIR expressions.
 pub fn lower_hir(
     unifier: Unifier,
-    table: Table,
-    program: hir::Program,
+    types: TableBuilder,
+    mut program: hir::Program,
 ) -> LowerResult<(mir::Program, Table)> {
-    let mut lcx = LowerContext::new(unifier, table);
+    // The spans are kept in the context to track the location of runtime errors.
+    let spans = std::mem::replace(&mut program.spans, IndexMap::new());
+    let mut lcx = LowerContext::new(unifier, types, spans);
     let program = lcx.lower(program)?;
 
     Ok((program, lcx.table))
@@ -0,0 +1,28 @@
+use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
+
+use pijama_hir as hir;
+use pijama_mir as mir;
+
+impl Lower for hir::AdtDef {
+    type Output = mir::AdtDef;
+
+    fn lower_with(self, lcx: &mut LowerContext) -> LowerResult<Self::Output> {
+        let variants = self
+            .variants
+            .into_iter()
+            .map(|variant| mir::Variant {
+                name: variant.name,
+                fields: variant
+                    .fields
+                    .into_iter()
+                    .map(|ty| lcx.instantiate(ty))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(mir::AdtDef {
+            name: self.name,
+            variants,
+        })
+    }
+}
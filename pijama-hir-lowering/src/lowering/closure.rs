@@ -0,0 +1,216 @@
+//! Closure conversion.
+//!
+//! Each lambda is lifted into a new function that receives the values of the locals it captures
+//! in an environment. The environment is a tuple that is built when the lambda is evaluated, and
+//! the lifted function binds each captured local to a projection of it before evaluating the body.
+
+use crate::{
+    context::LowerContext,
+    error::LowerResult,
+    lowering::{pattern::wrap_lets, Lower},
+};
+
+use pijama_hir as hir;
+use pijama_mir as mir;
+use pijama_ty::{inference, ty::Ty};
+use pijama_utils::index::IndexMap;
+
+/// A lambda that must be lifted into a closure.
+pub(crate) struct Closure {
+    /// The parameters of the lambda.
+    params: Vec<hir::Local>,
+    /// The locals captured by the lambda, in the order they are stored in the environment.
+    captures: Vec<hir::Local>,
+    /// The type of the value returned by the lambda.
+    return_ty: inference::Ty,
+    /// The body of the lambda.
+    body: hir::Expr,
+    /// The types of the locals of the function where the lambda is defined.
+    locals: IndexMap<hir::Local, inference::Ty>,
+}
+
+/// Lower a lambda into a closure.
+///
+/// The lambda is scheduled to be lifted and the closure is built with the values of the locals
+/// captured by it.
+pub(crate) fn lower_lambda(
+    params: Vec<hir::Local>,
+    return_ty: inference::Ty,
+    body: hir::Expr,
+    lcx: &mut LowerContext,
+) -> LowerResult<mir::ExprKind> {
+    let captures = free_locals(&params, &body);
+
+    let atoms = captures
+        .iter()
+        .map(|&local| Ok(mir::Atom::Name(mir::Name::Local(lcx.lower(local)?))))
+        .collect::<LowerResult<Vec<_>>>()?;
+
+    let closure = Closure {
+        params,
+        captures,
+        return_ty,
+        body,
+        locals: lcx.hir_locals().clone(),
+    };
+
+    Ok(mir::ExprKind::Closure {
+        func: lcx.closure_instance(closure),
+        captures: atoms,
+    })
+}
+
+impl Lower for Closure {
+    type Output = mir::Func;
+
+    fn lower_with(self, lcx: &mut LowerContext) -> LowerResult<Self::Output> {
+        lcx.enter_func(self.locals);
+
+        // The parameters must be the first locals.
+        let arity = self.params.len();
+        for param in self.params {
+            lcx.lower(param)?;
+        }
+
+        // The environment is the local right after the parameters.
+        let env = if self.captures.is_empty() {
+            None
+        } else {
+            let fields = self
+                .captures
+                .iter()
+                .map(|&local| lcx.hir_local_ty(local))
+                .collect();
+
+            Some(lcx.store_local_ty(Ty::Tuple { fields }))
+        };
+
+        // Bind each captured local to its value in the environment.
+        let mut binds = Vec::with_capacity(self.captures.len());
+        for (index, capture) in self.captures.into_iter().enumerate() {
+            let ty = lcx.hir_local_ty(capture);
+            let rhs = mir::Expr {
+                id: lcx.table.store_ty(ty),
+                kind: mir::ExprKind::Projection {
+                    tuple: mir::Atom::Name(mir::Name::Local(env.unwrap())),
+                    index,
+                },
+            };
+
+            binds.push((lcx.lower(capture)?, rhs));
+        }
+
+        let return_ty = lcx.lower(self.return_ty)?;
+
+        let mut body = lcx.lower(self.body)?;
+        let body_ty = lcx.get_expr_ty(body.id).unwrap().clone();
+        body.kind = wrap_lets(binds, body.kind, &body_ty, lcx);
+
+        Ok(mir::Func {
+            arity,
+            env,
+            locals: lcx.get_local_types(),
+            return_ty,
+            body,
+        })
+    }
+}
+
+/// Find the locals used by a lambda that are not bound by it, in order of appearance.
+fn free_locals(params: &[hir::Local], body: &hir::Expr) -> Vec<hir::Local> {
+    let mut bound = params.to_vec();
+    let mut used = Vec::new();
+
+    collect_locals(body, &mut bound, &mut used);
+
+    used.retain(|local| !bound.contains(local));
+    used
+}
+
+/// Collect the locals bound inside an expression and the locals used by it.
+///
+/// Locals cannot be rebound, so a local used by an expression is free only if it is not bound
+/// anywhere inside it.
+fn collect_locals(expr: &hir::Expr, bound: &mut Vec<hir::Local>, used: &mut Vec<hir::Local>) {
+    match &expr.kind {
+        hir::ExprKind::Atom(hir::Atom::Name(hir::Name::Local(local))) => use_local(*local, used),
+        hir::ExprKind::Atom(_) | hir::ExprKind::Instantiate { .. } => (),
+        hir::ExprKind::Let { lhs, rhs, body } => {
+            bound.push(*lhs);
+            collect_locals(rhs, bound, used);
+            collect_locals(body, bound, used);
+        }
+        hir::ExprKind::Call { func, args } => {
+            collect_locals(func, bound, used);
+            for arg in args {
+                collect_locals(arg, bound, used);
+            }
+        }
+        hir::ExprKind::UnaryOp { op, .. } => collect_locals(op, bound, used),
+        hir::ExprKind::BinaryOp {
+            left_op, right_op, ..
+        } => {
+            collect_locals(left_op, bound, used);
+            collect_locals(right_op, bound, used);
+        }
+        hir::ExprKind::Cond {
+            cond,
+            do_branch,
+            else_branch,
+        } => {
+            collect_locals(cond, bound, used);
+            collect_locals(do_branch, bound, used);
+            collect_locals(else_branch, bound, used);
+        }
+        hir::ExprKind::Tuple { fields } | hir::ExprKind::Construct { args: fields, .. } => {
+            for field in fields {
+                collect_locals(field, bound, used);
+            }
+        }
+        hir::ExprKind::Record { fields, .. } => {
+            for (_, field) in fields {
+                collect_locals(field, bound, used);
+            }
+        }
+        hir::ExprKind::Projection { tuple: record, .. } | hir::ExprKind::Field { record, .. } => {
+            collect_locals(record, bound, used)
+        }
+        hir::ExprKind::Update { record, fields } => {
+            collect_locals(record, bound, used);
+            for (_, field) in fields {
+                collect_locals(field, bound, used);
+            }
+        }
+        hir::ExprKind::Match { scrutinee, arms } => {
+            collect_locals(scrutinee, bound, used);
+            for arm in arms {
+                collect_bindings(&arm.pattern, bound);
+                collect_locals(&arm.body, bound, used);
+            }
+        }
+        hir::ExprKind::Lambda { params, body, .. } => {
+            bound.extend_from_slice(params);
+            collect_locals(body, bound, used);
+        }
+    }
+}
+
+/// Record that a local is used, if it was not recorded already.
+fn use_local(local: hir::Local, used: &mut Vec<hir::Local>) {
+    if !used.contains(&local) {
+        used.push(local);
+    }
+}
+
+/// Collect the locals bound by a pattern.
+fn collect_bindings(pattern: &hir::Pattern, bound: &mut Vec<hir::Local>) {
+    match &pattern.kind {
+        hir::PatternKind::Wildcard | hir::PatternKind::Literal(_) => (),
+        hir::PatternKind::Binding(local) => bound.push(*local),
+        hir::PatternKind::Constructor { fields, .. } | hir::PatternKind::Tuple { fields } => {
+            for field in fields {
+                collect_bindings(field, bound);
+            }
+        }
+    }
+}
@@ -1,7 +1,12 @@
-use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
+use crate::{
+    context::LowerContext,
+    error::LowerResult,
+    lowering::{closure::lower_lambda, pattern::lower_match, Lower},
+};
 
 use pijama_hir as hir;
 use pijama_mir as mir;
+use pijama_ty::{base::BaseTy, ty::Ty, ExprId};
 
 impl Lower for hir::Expr {
     type Output = mir::Expr;
@@ -10,15 +15,29 @@ impl Lower for hir::Expr {
         let mut binds = Vec::new();
 
         let mut kind = match self.kind {
-            hir::ExprKind::Atom(atom) => mir::ExprKind::Atom(lcx.lower(atom)?),
+            hir::ExprKind::Atom(atom) => {
+                let atom = match atom {
+                    hir::Atom::Literal(literal) => mir::Atom::Literal(lcx.lower(literal)?),
+                    hir::Atom::Name(name) => mir::Atom::Name(lower_name(name, self.id, lcx)?),
+                };
+
+                mir::ExprKind::Atom(atom)
+            }
+            hir::ExprKind::Instantiate { func, .. } => {
+                let name = lower_name(hir::Name::FuncPtr(func), self.id, lcx)?;
+                mir::ExprKind::Atom(mir::Atom::Name(name))
+            }
             hir::ExprKind::Let { lhs, rhs, body } => mir::ExprKind::Let {
                 lhs: lcx.lower(lhs)?,
                 rhs: lcx.lower(rhs)?,
                 body: lcx.lower(body)?,
             },
             hir::ExprKind::Call { func, args } => {
-                // FIXME: allow arbitrary funcs
-                let func = lcx.lower(func)?;
+                // The called function is bound to a local if it is not a name already.
+                let func = match lower_into_atom(*func, lcx, &mut binds)? {
+                    mir::Atom::Name(name) => name,
+                    mir::Atom::Literal(_) => unreachable!("Literals cannot be called."),
+                };
 
                 let args = args
                     .into_iter()
@@ -35,7 +54,40 @@ impl Lower for hir::Expr {
 
                 let op = lower_into_atom(*op, lcx, &mut binds)?;
 
-                mir::ExprKind::UnaryOp { un_op, op }
+                mir::ExprKind::UnaryOp {
+                    un_op,
+                    span: lcx.get_span(self.id),
+                    op,
+                }
+            }
+            // Logical operators only evaluate the right operand if the left one does not determine
+            // the result. So `a && b` is lowered as `if a do b else false end` and `a || b` is
+            // lowered as `if a do true else b end`.
+            hir::ExprKind::BinaryOp {
+                bin_op: bin_op @ (hir::BinOp::And | hir::BinOp::Or),
+                left_op,
+                right_op,
+            } => {
+                let cond = lower_into_atom(*left_op, lcx, &mut binds)?;
+                let right_op = lcx.lower(*right_op)?;
+
+                let is_and = matches!(bin_op, hir::BinOp::And);
+                let literal = mir::Expr {
+                    id: lcx.table.store_ty(Ty::Base(BaseTy::Bool)),
+                    kind: mir::ExprKind::Atom(mir::Atom::Literal((!is_and).into())),
+                };
+
+                let (do_branch, else_branch) = if is_and {
+                    (right_op, literal)
+                } else {
+                    (literal, right_op)
+                };
+
+                mir::ExprKind::Cond {
+                    cond,
+                    do_branch: Box::new(do_branch),
+                    else_branch: Box::new(else_branch),
+                }
             }
             hir::ExprKind::BinaryOp {
                 bin_op,
@@ -48,8 +100,9 @@ impl Lower for hir::Expr {
                     hir::BinOp::Mul => mir::BinOp::Mul,
                     hir::BinOp::Div => mir::BinOp::Div,
                     hir::BinOp::Rem => mir::BinOp::Rem,
-                    hir::BinOp::And => mir::BinOp::And,
-                    hir::BinOp::Or => mir::BinOp::Or,
+                    hir::BinOp::And | hir::BinOp::Or => {
+                        unreachable!("Logical operators are lowered as conditionals.")
+                    }
                     hir::BinOp::Eq => mir::BinOp::Eq,
                     hir::BinOp::Neq => mir::BinOp::Neq,
                     hir::BinOp::Lt => mir::BinOp::Lt,
@@ -63,6 +116,7 @@ impl Lower for hir::Expr {
 
                 mir::ExprKind::BinaryOp {
                     bin_op,
+                    span: lcx.get_span(self.id),
                     left_op,
                     right_op,
                 }
@@ -84,9 +138,93 @@ impl Lower for hir::Expr {
 
                 mir::ExprKind::Tuple { fields }
             }
+            hir::ExprKind::Projection { tuple, index } => mir::ExprKind::Projection {
+                tuple: lower_into_atom(*tuple, lcx, &mut binds)?,
+                index,
+            },
+            hir::ExprKind::Record { fields, .. } => {
+                // The fields are evaluated in the order they were written.
+                let mut fields = fields
+                    .into_iter()
+                    .map(|(pos, expr)| Ok((pos, lower_into_atom(expr, lcx, &mut binds)?)))
+                    .collect::<LowerResult<Vec<_>>>()?;
+
+                fields.sort_by_key(|(pos, _)| *pos);
+
+                mir::ExprKind::Record {
+                    fields: fields.into_iter().map(|(_, atom)| atom).collect(),
+                }
+            }
+            hir::ExprKind::Field { record, name } => {
+                let record_ty = lcx.get_hir_expr_ty(record.id).unwrap();
+
+                mir::ExprKind::Projection {
+                    tuple: lower_into_atom(*record, lcx, &mut binds)?,
+                    index: field_index(&record_ty, &name),
+                }
+            }
+            hir::ExprKind::Update { record, fields } => {
+                let record_ty = lcx.get_hir_expr_ty(record.id).unwrap();
+                let record = lower_into_atom(*record, lcx, &mut binds)?;
+
+                let fields_ty = match record_ty {
+                    Ty::Record { fields, .. } => fields,
+                    _ => unreachable!("Only records can be updated."),
+                };
+
+                let mut new_fields = Vec::with_capacity(fields.len());
+                for (name, expr) in fields {
+                    new_fields.push((name, lower_into_atom(expr, lcx, &mut binds)?));
+                }
+
+                // The fields that are not updated are projected from the original record.
+                let mut fields = Vec::with_capacity(fields_ty.len());
+                for (index, (name, field_ty)) in fields_ty.into_iter().enumerate() {
+                    let field = match new_fields.iter().position(|(new, _)| *new == name) {
+                        Some(pos) => new_fields.swap_remove(pos).1,
+                        None => {
+                            let local = lcx.store_local_ty(field_ty.clone());
+                            let projection = mir::Expr {
+                                id: lcx.table.store_ty(field_ty),
+                                kind: mir::ExprKind::Projection {
+                                    tuple: record.clone(),
+                                    index,
+                                },
+                            };
+                            binds.push((local, projection));
+
+                            mir::Atom::Name(mir::Name::Local(local))
+                        }
+                    };
+
+                    fields.push(field);
+                }
+
+                mir::ExprKind::Record { fields }
+            }
+            hir::ExprKind::Construct { adt, variant, args } => {
+                let args = args
+                    .into_iter()
+                    .map(|expr| lower_into_atom(expr, lcx, &mut binds))
+                    .collect::<LowerResult<Vec<_>>>()?;
+
+                mir::ExprKind::Construct { adt, variant, args }
+            }
+            hir::ExprKind::Match { scrutinee, arms } => {
+                let scrutinee_ty = lcx.get_hir_expr_ty(scrutinee.id).unwrap();
+                let scrutinee = lower_into_atom(*scrutinee, lcx, &mut binds)?;
+                let ty = lcx.get_hir_expr_ty(self.id).unwrap();
+
+                lower_match(scrutinee, scrutinee_ty, arms, ty, lcx)?
+            }
+            hir::ExprKind::Lambda {
+                params,
+                return_ty,
+                body,
+            } => lower_lambda(params, return_ty, *body, lcx)?,
         };
 
-        let ty = lcx.get_expr_ty(self.id).unwrap().clone();
+        let ty = lcx.get_hir_expr_ty(self.id).unwrap();
 
         for (lhs, rhs) in binds.into_iter().rev() {
             let body = mir::Expr {
@@ -101,7 +239,35 @@ impl Lower for hir::Expr {
             };
         }
 
-        Ok(mir::Expr { id: self.id, kind })
+        // Every MIR expression gets a new ID because a HIR expression can be lowered once for
+        // each instance of its function.
+        Ok(mir::Expr {
+            id: lcx.table.store_ty(ty),
+            kind,
+        })
+    }
+}
+
+/// Lower a name used by a HIR expression.
+///
+/// Functions are replaced by the instance used by the expression.
+fn lower_name(name: hir::Name, id: ExprId, lcx: &mut LowerContext) -> LowerResult<mir::Name> {
+    let name = match name {
+        hir::Name::Local(local) => mir::Name::Local(lcx.lower(local)?),
+        hir::Name::FuncPtr(func_id) => mir::Name::FuncPtr(lcx.func_instance_at(func_id, id)),
+    };
+
+    Ok(name)
+}
+
+/// Find the position of a field in the definition of a record.
+fn field_index(record_ty: &Ty, name: &str) -> usize {
+    match record_ty {
+        Ty::Record { fields, .. } => fields
+            .iter()
+            .position(|(field, _)| field == name)
+            .expect("Every accessed field should exist."),
+        _ => unreachable!("Only records have named fields."),
     }
 }
 
@@ -2,15 +2,17 @@ use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
 
 use pijama_hir as hir;
 use pijama_mir as mir;
+use pijama_utils::index::Index;
 
 impl Lower for hir::Func {
     type Output = mir::Func;
 
     fn lower_with(self, lcx: &mut LowerContext) -> LowerResult<Self::Output> {
-        for (_, ty) in self.locals {
-            // Lower the type of each local.
-            let ty = lcx.lower(ty)?;
-            lcx.store_local_ty(ty);
+        lcx.enter_func(self.locals);
+
+        // The parameters must be the first locals.
+        for index in 0..self.arity {
+            lcx.lower(hir::Local::new(index))?;
         }
 
         // Lower the return type.
@@ -21,6 +23,7 @@ impl Lower for hir::Func {
 
         Ok(mir::Func {
             arity: self.arity,
+            env: None,
             locals: lcx.get_local_types(),
             return_ty,
             body,
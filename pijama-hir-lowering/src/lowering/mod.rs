@@ -1,9 +1,10 @@
-mod atom;
+mod adt;
+pub(crate) mod closure;
 mod expr;
 mod func;
 mod literal;
 mod local;
-mod name;
+mod pattern;
 mod program;
 mod ty;
 
@@ -0,0 +1,360 @@
+use crate::{context::LowerContext, error::LowerResult};
+
+use pijama_hir as hir;
+use pijama_mir as mir;
+use pijama_ty::{base::BaseTy, ty::Ty, AdtId};
+use pijama_utils::span::Span;
+
+/// Lower the arms of a `match` expression into nested conditionals.
+///
+/// Each arm is lowered as a conditional that tests if the scrutinee matches the pattern of the
+/// arm. If it does, the locals of the pattern are bound and the body of the arm is evaluated.
+/// Otherwise, the next arm is tested. The pattern of the last arm is not tested because the
+/// patterns are exhaustive, so it must match any value not matched by the previous arms.
+pub(crate) fn lower_match(
+    scrutinee: mir::Atom,
+    scrutinee_ty: Ty,
+    arms: Vec<hir::Arm>,
+    ty: Ty,
+    lcx: &mut LowerContext,
+) -> LowerResult<mir::ExprKind> {
+    let scrutinee = (scrutinee, scrutinee_ty);
+    let mut arms = arms.into_iter().rev();
+
+    let last_arm = arms
+        .next()
+        .expect("Every `match` expression should have at least one arm.");
+    let mut kind = lower_arm(&scrutinee, last_arm, None, &ty, lcx)?;
+
+    for arm in arms {
+        let else_branch = mir::Expr {
+            id: lcx.table.store_ty(ty.clone()),
+            kind,
+        };
+        kind = lower_arm(&scrutinee, arm, Some(else_branch), &ty, lcx)?;
+    }
+
+    Ok(kind)
+}
+
+/// A step to reach a part of the scrutinee.
+#[derive(Clone, PartialEq)]
+enum Access {
+    /// Access a field of a tuple.
+    Field(usize),
+    /// Access a field of a variant of an algebraic data type.
+    VariantField {
+        adt: AdtId,
+        variant: usize,
+        index: usize,
+    },
+}
+
+/// A test that must succeed for a part of the scrutinee to match a pattern.
+enum Test {
+    /// The value must be equal to a literal.
+    Literal(mir::Literal),
+    /// The value must have been built with the constructor of a variant.
+    Variant { adt: AdtId, variant: usize },
+}
+
+/// The tests and bindings of a pattern.
+///
+/// Each one of them has the path of accesses required to reach the part of the scrutinee it
+/// uses.
+#[derive(Default)]
+struct Destructuring {
+    /// The tests that must succeed for the pattern to match, in the order they must be
+    /// evaluated.
+    tests: Vec<(Vec<Access>, Test, Span)>,
+    /// The locals bound by the pattern.
+    bindings: Vec<(Vec<Access>, mir::Local)>,
+}
+
+/// Lower an arm of a `match` expression.
+///
+/// If the arm has an `else_branch`, it is evaluated when the pattern does not match.
+fn lower_arm(
+    scrutinee: &(mir::Atom, Ty),
+    arm: hir::Arm,
+    else_branch: Option<mir::Expr>,
+    ty: &Ty,
+    lcx: &mut LowerContext,
+) -> LowerResult<mir::ExprKind> {
+    let mut destructuring = Destructuring::default();
+    destructure(arm.pattern, Vec::new(), lcx, &mut destructuring)?;
+
+    // Bind the locals of the pattern before evaluating the body. The parts of the scrutinee are
+    // accessed again because the accesses done by the tests are not in scope here.
+    let mut accesses = Accesses::new(scrutinee);
+    let mut bindings = Vec::new();
+    for (path, local) in destructuring.bindings {
+        let (atom, atom_ty) = accesses.access(&path, lcx);
+        let rhs = mir::Expr {
+            id: lcx.table.store_ty(atom_ty),
+            kind: mir::ExprKind::Atom(atom),
+        };
+        bindings.push((local, rhs));
+    }
+    let mut binds = accesses.binds;
+    binds.extend(bindings);
+
+    let mut body = lcx.lower(arm.body)?;
+    body.kind = wrap_lets(binds, body.kind, ty, lcx);
+
+    let kind = match else_branch {
+        Some(else_branch) => {
+            // Bind the result of the tests to a local so it can be used as a condition.
+            let cond_ty = Ty::Base(BaseTy::Bool);
+            let cond = lower_tests(scrutinee, destructuring.tests, lcx);
+            let cond_local = lcx.store_local_ty(cond_ty);
+
+            let kind = mir::ExprKind::Cond {
+                cond: mir::Atom::Name(mir::Name::Local(cond_local)),
+                do_branch: Box::new(body),
+                else_branch: Box::new(else_branch),
+            };
+
+            wrap_lets(vec![(cond_local, cond)], kind, ty, lcx)
+        }
+        None => body.kind,
+    };
+
+    Ok(kind)
+}
+
+/// Collect the tests and bindings required to match the part of the scrutinee reached by `path`
+/// against a pattern.
+fn destructure(
+    pattern: hir::Pattern,
+    path: Vec<Access>,
+    lcx: &mut LowerContext,
+    destructuring: &mut Destructuring,
+) -> LowerResult<()> {
+    match pattern.kind {
+        hir::PatternKind::Wildcard => (),
+        hir::PatternKind::Binding(local) => {
+            let local = lcx.lower(local)?;
+            destructuring.bindings.push((path, local));
+        }
+        hir::PatternKind::Literal(literal) => {
+            let test = Test::Literal(lcx.lower(literal)?);
+            destructuring.tests.push((path, test, pattern.span));
+        }
+        hir::PatternKind::Constructor {
+            adt,
+            variant,
+            fields,
+        } => {
+            // The variant must be tested before accessing any of its fields.
+            let test = Test::Variant { adt, variant };
+            destructuring.tests.push((path.clone(), test, pattern.span));
+
+            for (index, field) in fields.into_iter().enumerate() {
+                let mut path = path.clone();
+                path.push(Access::VariantField {
+                    adt,
+                    variant,
+                    index,
+                });
+                destructure(field, path, lcx, destructuring)?;
+            }
+        }
+        hir::PatternKind::Tuple { fields } => {
+            for (index, field) in fields.into_iter().enumerate() {
+                let mut path = path.clone();
+                path.push(Access::Field(index));
+                destructure(field, path, lcx, destructuring)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The parts of the scrutinee that have been accessed.
+struct Accesses {
+    /// The scrutinee and its type.
+    scrutinee: (mir::Atom, Ty),
+    /// The parts that have been accessed with their paths and types.
+    cache: Vec<(Vec<Access>, mir::Atom, Ty)>,
+    /// The bindings for the accesses that have not been taken yet, in the order they must be
+    /// evaluated.
+    binds: Vec<(mir::Local, mir::Expr)>,
+}
+
+impl Accesses {
+    fn new(scrutinee: &(mir::Atom, Ty)) -> Self {
+        Self {
+            scrutinee: scrutinee.clone(),
+            cache: Vec::new(),
+            binds: Vec::new(),
+        }
+    }
+
+    /// Get an atom with the part of the scrutinee reached by a path and its type.
+    ///
+    /// Each part is bound to a local the first time it is accessed.
+    fn access(&mut self, path: &[Access], lcx: &mut LowerContext) -> (mir::Atom, Ty) {
+        let (last, parent_path) = match path.split_last() {
+            Some(split) => split,
+            None => return self.scrutinee.clone(),
+        };
+
+        if let Some((_, atom, ty)) = self.cache.iter().find(|(cached, ..)| cached == path) {
+            return (atom.clone(), ty.clone());
+        }
+
+        let (parent, parent_ty) = self.access(parent_path, lcx);
+
+        let (kind, ty) = match *last {
+            Access::Field(index) => {
+                let ty = match parent_ty {
+                    Ty::Tuple { mut fields } => fields.swap_remove(index),
+                    _ => unreachable!("Tuple patterns should only match tuples."),
+                };
+                let kind = mir::ExprKind::Projection {
+                    tuple: parent,
+                    index,
+                };
+
+                (kind, ty)
+            }
+            Access::VariantField {
+                adt,
+                variant,
+                index,
+            } => {
+                let ty = lcx.get_variant(adt, variant).fields[index].clone();
+                let kind = mir::ExprKind::VariantField {
+                    adt,
+                    variant,
+                    index,
+                    value: parent,
+                };
+
+                (kind, ty)
+            }
+        };
+
+        let local = lcx.store_local_ty(ty.clone());
+        let rhs = mir::Expr {
+            id: lcx.table.store_ty(ty.clone()),
+            kind,
+        };
+        self.binds.push((local, rhs));
+
+        let atom = mir::Atom::Name(mir::Name::Local(local));
+        self.cache.push((path.to_vec(), atom.clone(), ty.clone()));
+
+        (atom, ty)
+    }
+}
+
+/// Lower the tests of a pattern into a boolean expression that is true only if all of them
+/// succeed.
+///
+/// The tests are evaluated in order and the evaluation stops as soon as one of them fails. This
+/// guarantees that the fields of a variant are only accessed if the value has that variant.
+fn lower_tests(
+    scrutinee: &(mir::Atom, Ty),
+    tests: Vec<(Vec<Access>, Test, Span)>,
+    lcx: &mut LowerContext,
+) -> mir::Expr {
+    let bool_ty = Ty::Base(BaseTy::Bool);
+    let mut accesses = Accesses::new(scrutinee);
+
+    // Each test is lowered into a comparison and the bindings required to evaluate it.
+    let mut steps = Vec::new();
+    for (path, test, span) in tests {
+        let (atom, _) = accesses.access(&path, lcx);
+
+        let (left_op, literal) = match test {
+            Test::Literal(literal) => (atom, literal),
+            Test::Variant { adt, variant } => {
+                // Compare the tag of the value with the position of the variant.
+                let int_ty = Ty::Base(BaseTy::Int);
+                let tag = lcx.store_local_ty(int_ty.clone());
+                let rhs = mir::Expr {
+                    id: lcx.table.store_ty(int_ty),
+                    kind: mir::ExprKind::Tag { adt, value: atom },
+                };
+                accesses.binds.push((tag, rhs));
+
+                let atom = mir::Atom::Name(mir::Name::Local(tag));
+                (atom, (variant as i64).into())
+            }
+        };
+
+        let test = mir::ExprKind::BinaryOp {
+            bin_op: mir::BinOp::Eq,
+            span,
+            left_op,
+            right_op: mir::Atom::Literal(literal),
+        };
+
+        steps.push((std::mem::take(&mut accesses.binds), test));
+    }
+
+    // `test && rest` is lowered as `let local = test; if local do rest else false end`.
+    let mut kind = None;
+    for (mut binds, test) in steps.into_iter().rev() {
+        let test = match kind {
+            None => test,
+            Some(rest) => {
+                let rest = mir::Expr {
+                    id: lcx.table.store_ty(bool_ty.clone()),
+                    kind: rest,
+                };
+                let literal = mir::Expr {
+                    id: lcx.table.store_ty(bool_ty.clone()),
+                    kind: mir::ExprKind::Atom(mir::Atom::Literal(false.into())),
+                };
+                let test = mir::Expr {
+                    id: lcx.table.store_ty(bool_ty.clone()),
+                    kind: test,
+                };
+                let local = lcx.store_local_ty(bool_ty.clone());
+                binds.push((local, test));
+
+                mir::ExprKind::Cond {
+                    cond: mir::Atom::Name(mir::Name::Local(local)),
+                    do_branch: Box::new(rest),
+                    else_branch: Box::new(literal),
+                }
+            }
+        };
+
+        kind = Some(wrap_lets(binds, test, &bool_ty, lcx));
+    }
+
+    mir::Expr {
+        id: lcx.table.store_ty(bool_ty),
+        // A pattern without tests always matches.
+        kind: kind.unwrap_or(mir::ExprKind::Atom(mir::Atom::Literal(true.into()))),
+    }
+}
+
+/// Wrap an expression with a `let` binding for each element of `binds`, where the first element
+/// is bound first.
+pub(crate) fn wrap_lets(
+    binds: Vec<(mir::Local, mir::Expr)>,
+    mut kind: mir::ExprKind,
+    ty: &Ty,
+    lcx: &mut LowerContext,
+) -> mir::ExprKind {
+    for (lhs, rhs) in binds.into_iter().rev() {
+        let body = mir::Expr {
+            id: lcx.table.store_ty(ty.clone()),
+            kind,
+        };
+
+        kind = mir::ExprKind::Let {
+            lhs,
+            rhs: Box::new(rhs),
+            body: Box::new(body),
+        };
+    }
+
+    kind
+}
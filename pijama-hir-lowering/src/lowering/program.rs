@@ -1,4 +1,8 @@
-use crate::{context::LowerContext, error::LowerResult, lowering::Lower};
+use crate::{
+    context::{LowerContext, PendingFunc},
+    error::LowerResult,
+    lowering::Lower,
+};
 
 use pijama_hir as hir;
 use pijama_mir as mir;
@@ -10,12 +14,35 @@ impl Lower for hir::Program {
     fn lower_with(self, lcx: &mut LowerContext) -> LowerResult<Self::Output> {
         let mut functions = IndexMap::new();
 
-        for (_func_id, func) in self.functions {
-            // Lower each function.
-            let func = lcx.lower(func)?;
-            functions.insert(func);
+        // The algebraic data types are lowered first because their definitions are used to lower
+        // `match` expressions.
+        for (_, adt) in self.adts {
+            let adt = lcx.lower(adt)?;
+            lcx.adts.insert(adt);
         }
 
-        Ok(mir::Program { functions })
+        // Start with the main function, every other function is lowered only if it is used.
+        let main_id = lcx.func_instance(hir::FuncId::main(), Vec::new());
+        assert_eq!(main_id, mir::FuncId::main());
+
+        while let Some((func, func_id)) = lcx.next_func() {
+            let func = match func {
+                // Lower each instance of the function.
+                PendingFunc::Instance(hir_id) => {
+                    let func = self.functions.get(hir_id).unwrap().clone();
+                    lcx.lower(func)?
+                }
+                PendingFunc::Closure(closure) => lcx.lower(closure)?,
+            };
+            assert_eq!(
+                func_id,
+                functions.insert(func),
+                "Functions should be lowered in order."
+            );
+        }
+
+        let adts = std::mem::replace(&mut lcx.adts, IndexMap::new());
+
+        Ok(mir::Program { functions, adts })
     }
 }
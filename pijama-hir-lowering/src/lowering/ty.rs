@@ -6,7 +6,7 @@ impl Lower for inference::Ty {
     type Output = ty::Ty;
 
     fn lower_with(self, lcx: &mut LowerContext) -> LowerResult<Self::Output> {
-        // Use the unifier to instantiate the type.
-        Ok(lcx.unifier.instantiate(self))
+        // Use the unifier to instantiate the type for the current instance.
+        Ok(lcx.instantiate(self))
     }
 }
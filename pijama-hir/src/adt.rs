@@ -0,0 +1,23 @@
+use pijama_ty::inference::Ty;
+
+/// An algebraic data type.
+///
+/// Each type has a globally unique [pijama_ty::AdtId] assigned to it.
+#[derive(Debug, Clone)]
+pub struct AdtDef {
+    /// The name of the type.
+    pub name: String,
+    /// The variants of the type.
+    ///
+    /// The position of each variant is used to identify it.
+    pub variants: Vec<Variant>,
+}
+
+/// A variant of an algebraic data type.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    /// The name of the variant's constructor.
+    pub name: String,
+    /// The types of the fields of the variant.
+    pub fields: Vec<Ty>,
+}
@@ -3,7 +3,7 @@ use crate::{literal::Literal, name::Name};
 /// An atomic value.
 ///
 /// Atoms represent values that do not need to be computed.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Atom {
     /// A value that can be interpreted literally.
     Literal(Literal),
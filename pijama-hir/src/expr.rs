@@ -1,22 +1,24 @@
 use crate::{
     atom::Atom,
-    name::{Local, Name},
+    func::FuncId,
+    name::Local,
+    pattern::Arm,
     prim_op::{BinOp, UnOp},
 };
 
-use pijama_ty::ExprId;
+use pijama_ty::{inference::Ty, AdtId, ExprId};
 
 /// An expression.
 ///
 /// This type tries to avoid nesting as much as possible by using atoms for all the control-flow
 /// related expressions.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expr {
     pub id: ExprId,
     pub kind: ExprKind,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExprKind {
     /// An atomic expression.
     Atom(Atom),
@@ -38,10 +40,10 @@ pub enum ExprKind {
     },
     /// A function call.
     Call {
-        /// The name of the called function.
+        /// The called function.
         ///
-        /// This can be a local because of expressions like: `(if cond do f else g end)(arg)`.
-        func: Name,
+        /// This can be any expression, as in `(if cond do f else g end)(arg)` or `f(1)(2)`.
+        func: Box<Expr>,
         /// The arguments of the call.
         args: Vec<Expr>,
     },
@@ -73,4 +75,68 @@ pub enum ExprKind {
     Tuple {
         fields: Vec<Expr>,
     },
+    /// A tuple projection.
+    Projection {
+        /// The projected tuple.
+        tuple: Box<Expr>,
+        /// The index of the field.
+        index: usize,
+    },
+    /// A value of an algebraic data type built with the constructor of one of its variants.
+    Construct {
+        /// The ID of the type.
+        adt: AdtId,
+        /// The position of the variant in the type's definition.
+        variant: usize,
+        /// The values of the fields of the variant.
+        args: Vec<Expr>,
+    },
+    /// A record.
+    Record {
+        /// The type of the record.
+        ty: Ty,
+        /// The value of each field with its position in the record's definition, in the order
+        /// they were written.
+        fields: Vec<(usize, Expr)>,
+    },
+    /// A record field access.
+    Field {
+        /// The accessed record.
+        record: Box<Expr>,
+        /// The name of the field.
+        name: String,
+    },
+    /// A functional record update.
+    Update {
+        /// The updated record.
+        record: Box<Expr>,
+        /// The name and new value of each updated field.
+        fields: Vec<(String, Expr)>,
+    },
+    /// A `match` expression.
+    Match {
+        /// The expression being matched.
+        scrutinee: Box<Expr>,
+        /// The arms of the expression, tested in order.
+        arms: Vec<Arm>,
+    },
+    /// An anonymous function.
+    ///
+    /// The parameters and the locals bound inside the body are locals of the function where the
+    /// lambda is defined. The body can use any other local in scope.
+    Lambda {
+        /// The locals for the parameters of the function.
+        params: Vec<Local>,
+        /// The type of the value returned by the function.
+        return_ty: Ty,
+        /// The body of the function.
+        body: Box<Expr>,
+    },
+    /// A function with explicit type arguments.
+    Instantiate {
+        /// The ID of the function.
+        func: FuncId,
+        /// The type arguments, in the same order as the type parameters of the function.
+        ty_args: Vec<Ty>,
+    },
 }
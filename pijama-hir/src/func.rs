@@ -1,22 +1,36 @@
 use crate::{expr::Expr, name::Local};
 
 use pijama_ty::inference::Ty;
-use pijama_utils::{index::IndexMap, new_index};
+use pijama_utils::{index::IndexMap, new_index, span::Span};
 
 new_index! {
     #[doc = "A function's ID.\n\nBy convention, the first ID corresponds to the `main` function of the program."]
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
     FuncId
 }
 
+impl FuncId {
+    /// The `FuncId` for the main function.
+    pub const fn main() -> Self {
+        FuncId(0)
+    }
+}
+
 /// A function.
 ///
 /// In this IR, functions are C-like, which means they are not closures and all of them are
-/// globally defined. Each function has a globally unique [FuncId] assigned to it.
-#[derive(Debug)]
+/// globally defined. Each function has a globally unique [FuncId] assigned to it. Anonymous
+/// functions are expressions instead, see [crate::ExprKind::Lambda].
+#[derive(Debug, Clone)]
 pub struct Func {
+    /// The name of the function.
+    pub name: String,
     /// The number of parameters of the function.
     pub arity: usize,
+    /// The type parameters of the function, in the order they were declared.
+    ///
+    /// Each one of them is a [Ty::Param].
+    pub ty_params: Vec<Ty>,
     /// The local values of the function with their types.
     ///
     /// The first `arity` locals correspond to the function's parameters.
@@ -25,4 +39,8 @@ pub struct Func {
     pub return_ty: Ty,
     /// The body of the function.
     pub body: Expr,
+    /// The location of each local of the function in the source code.
+    pub local_spans: IndexMap<Local, Span>,
+    /// The location of the function's definition in the source code.
+    pub span: Span,
 }
@@ -1,15 +1,19 @@
+mod adt;
 mod atom;
 mod expr;
 mod func;
 mod literal;
 mod name;
+mod pattern;
 mod prim_op;
 mod program;
 
+pub use adt::{AdtDef, Variant};
 pub use atom::Atom;
 pub use expr::{Expr, ExprKind};
 pub use func::{Func, FuncId};
 pub use literal::Literal;
 pub use name::{Local, Name};
+pub use pattern::{Arm, Pattern, PatternKind};
 pub use prim_op::{BinOp, UnOp};
 pub use program::Program;
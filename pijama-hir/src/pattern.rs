@@ -0,0 +1,47 @@
+use crate::{expr::Expr, literal::Literal, name::Local};
+
+use pijama_ty::AdtId;
+use pijama_utils::span::Span;
+
+/// A pattern tested by a `match` expression.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    /// The location of the pattern in the source code.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    /// A pattern that matches any value.
+    Wildcard,
+    /// A pattern that matches any value and binds it to a local.
+    Binding(Local),
+    /// A pattern that only matches a literal value.
+    Literal(Literal),
+    /// A pattern that matches the values built with the constructor of a variant.
+    Constructor {
+        /// The ID of the type.
+        adt: AdtId,
+        /// The position of the variant in the type's definition.
+        variant: usize,
+        /// The patterns for each field of the variant.
+        fields: Vec<Pattern>,
+    },
+    /// A pattern that destructures a tuple.
+    Tuple {
+        /// The patterns for each field of the tuple.
+        fields: Vec<Pattern>,
+    },
+}
+
+/// An arm of a `match` expression.
+#[derive(Debug, Clone)]
+pub struct Arm {
+    /// The pattern tested against the scrutinee.
+    pub pattern: Pattern,
+    /// The expression evaluated if the pattern matches.
+    ///
+    /// The locals bound by the pattern are only valid inside this expression.
+    pub body: Expr,
+}
@@ -1,5 +1,5 @@
 /// A primitive unary operator.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum UnOp {
     /// The logical negation operator.
     Not,
@@ -8,7 +8,7 @@ pub enum UnOp {
 }
 
 /// A primitive binary operator.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum BinOp {
     /// The arithmetic addition operator.
     Add,
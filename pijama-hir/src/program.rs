@@ -1,10 +1,18 @@
-use crate::func::{Func, FuncId};
+use crate::{
+    adt::AdtDef,
+    func::{Func, FuncId},
+};
 
-use pijama_utils::index::IndexMap;
+use pijama_ty::{AdtId, ExprId};
+use pijama_utils::{index::IndexMap, span::Span};
 
 /// A program.
 #[derive(Debug)]
 pub struct Program {
     /// The functions of the program.
     pub functions: IndexMap<FuncId, Func>,
+    /// The algebraic data types of the program.
+    pub adts: IndexMap<AdtId, AdtDef>,
+    /// The location of each expression of the program in the source code.
+    pub spans: IndexMap<ExprId, Span>,
 }
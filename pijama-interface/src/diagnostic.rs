@@ -0,0 +1,142 @@
+//! Utilities to report errors to the user.
+use pijama_ast_lowering::error::LowerError;
+use pijama_parser::error::ParseError;
+use pijama_tycheck::error::TyError;
+use pijama_utils::span::Span;
+
+use std::{fmt, path::PathBuf};
+
+/// A problem found while compiling a program.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// A human readable description of the problem.
+    pub message: String,
+    /// The location of the problem in the source code.
+    ///
+    /// This span is a dummy if the problem cannot be tracked to a specific location.
+    pub span: Span,
+}
+
+impl Diagnostic {
+    /// Create a new diagnostic.
+    pub fn new(message: impl ToString, span: Span) -> Self {
+        Self {
+            message: message.to_string(),
+            span,
+        }
+    }
+}
+
+/// The diagnostics emitted while compiling a source file.
+///
+/// This type owns the source code of the file so it can render each diagnostic with the snippet
+/// of code that caused it.
+#[derive(Debug)]
+pub struct Diagnostics {
+    /// The path of the file being compiled.
+    path: PathBuf,
+    /// The source code of the file being compiled.
+    source: String,
+    /// The diagnostics for this file.
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Create a new collection of diagnostics for a source file.
+    pub fn new(path: PathBuf, source: String, items: Vec<Diagnostic>) -> Self {
+        Self {
+            path,
+            source,
+            items,
+        }
+    }
+
+    /// Get the diagnostics for this file.
+    pub fn items(&self) -> &[Diagnostic] {
+        &self.items
+    }
+
+    /// Render a single diagnostic.
+    fn fmt_diagnostic(&self, diagnostic: &Diagnostic, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "error: {}", diagnostic.message)?;
+
+        // Diagnostics without a location only show the path of the file.
+        if diagnostic.span.is_dummy() {
+            return writeln!(f, " --> {}", self.path.display());
+        }
+
+        let Span { start, end } = diagnostic.span;
+        let (line, column) = diagnostic.span.position(&self.source);
+        let (line_start, line_end) = diagnostic.span.line_bounds(&self.source);
+        let line_text = &self.source[line_start..line_end];
+
+        // Only the first line of the span is underlined.
+        let end = end.max(start).min(line_end);
+
+        // Tabs are expanded so the underline is aligned with the snippet.
+        let expand = |text: &str| text.replace('\t', "    ");
+        let padding = expand(&self.source[line_start..start.min(line_end)])
+            .chars()
+            .count();
+        let width = expand(&self.source[start.min(line_end)..end])
+            .chars()
+            .count()
+            .max(1);
+
+        let gutter = " ".repeat(line.to_string().len());
+
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter,
+            self.path.display(),
+            line,
+            column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line, expand(line_text))?;
+        writeln!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(padding),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.items {
+            self.fmt_diagnostic(diagnostic, f)?;
+            writeln!(f)?;
+        }
+
+        let count = self.items.len();
+        write!(
+            f,
+            "error: could not compile `{}` due to {} previous error{}",
+            self.path.display(),
+            count,
+            if count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+impl<'source> From<ParseError<'source>> for Diagnostic {
+    fn from(error: ParseError<'source>) -> Self {
+        Self::new(error.kind, error.span)
+    }
+}
+
+impl<'source> From<LowerError<'source>> for Diagnostic {
+    fn from(error: LowerError<'source>) -> Self {
+        Self::new(error.kind, error.span)
+    }
+}
+
+impl From<TyError> for Diagnostic {
+    fn from(error: TyError) -> Self {
+        Self::new(error.kind, error.span)
+    }
+}
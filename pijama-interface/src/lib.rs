@@ -1,4 +1,11 @@
-use pijama_ty::inference::TyContext;
+mod diagnostic;
+
+pub use diagnostic::{Diagnostic, Diagnostics};
+pub use pijama_llvm::Emit;
+
+use pijama_ast_lowering::error::LowerErrorKind;
+use pijama_ty::inference::{TyContext, VarNames};
+use pijama_utils::{show::Show, span::Span};
 
 use std::{
     ffi::OsStr,
@@ -14,6 +21,33 @@ pub struct Config {
     pub path: PathBuf,
     /// Generate a binary file.
     pub codegen: bool,
+    /// The kind of file generated by the backend.
+    ///
+    /// Only object files are linked into an executable. This is ignored if `run` is enabled.
+    pub emit: Emit,
+    /// Run the program just in time and print its result instead of generating a binary file.
+    ///
+    /// This is only done if `codegen` is enabled.
+    pub run: bool,
+    /// Run the program with the MIR interpreter instead of LLVM.
+    ///
+    /// This is only done if `codegen` and `run` are enabled.
+    pub interpret: bool,
+    /// Print the infered type of each function.
+    pub print_signatures: bool,
+    /// Check for integer overflows and divisions by zero at runtime.
+    pub checked: bool,
+}
+
+/// The result of a successful compilation.
+#[derive(Debug)]
+pub enum Status {
+    /// The program was compiled without running it.
+    Compiled,
+    /// The program was run and returned this value.
+    Returned(String),
+    /// The program was run and stopped because of this runtime error.
+    Failed(String),
 }
 
 /// The compiler.
@@ -28,47 +62,157 @@ impl Compiler {
     }
 
     /// Run the compiler with a specific configuration.
-    pub fn run(self, config: Config) {
+    ///
+    /// If compilation fails, return the diagnostics explaining why. Errors found while running
+    /// the program are not diagnostics, they are returned as a [Status::Failed] instead.
+    pub fn run(self, config: Config) -> Result<Status, Diagnostics> {
         // Read the source code to a string.
-        let source = read_to_string(&config.path).unwrap();
+        let source = match read_to_string(&config.path) {
+            Ok(source) => source,
+            Err(err) => {
+                let diagnostic =
+                    Diagnostic::new(format!("could not read file: {}", err), Span::dummy());
+                return Err(Diagnostics::new(
+                    config.path,
+                    String::new(),
+                    vec![diagnostic],
+                ));
+            }
+        };
+
+        self.compile(&config, &source)
+            .map_err(|diagnostics| Diagnostics::new(config.path, source, diagnostics))
+    }
 
+    /// Compile the source code of a file.
+    fn compile(&self, config: &Config, source: &str) -> Result<Status, Vec<Diagnostic>> {
         // Parse the source code.
-        let ast = pijama_parser::parse(&source).unwrap();
+        let (ast, parse_errors) = pijama_parser::parse(source);
+
+        let mut diagnostics = parse_errors
+            .into_iter()
+            .map(Diagnostic::from)
+            .collect::<Vec<_>>();
 
         // Create a new typing context.
         let tcx = TyContext::new();
 
-        // Lower the AST.
-        let hir = pijama_ast_lowering::lower_ast(&tcx, ast).unwrap();
+        // Lower the AST. This is done even if there are syntax errors to report as many errors as
+        // possible.
+        let hir = match pijama_ast_lowering::lower_ast(&tcx, ast) {
+            Ok(hir) => hir,
+            Err(errors) => {
+                // Syntax errors were already reported by the parser. If the parser could not
+                // recover, the main function might be missing because of them.
+                let has_syntax_errors = !diagnostics.is_empty();
+                diagnostics.extend(
+                    errors
+                        .into_iter()
+                        .filter(|error| match error.kind {
+                            LowerErrorKind::SyntaxError => false,
+                            LowerErrorKind::MainNotFound => !has_syntax_errors,
+                            _ => true,
+                        })
+                        .map(Diagnostic::from),
+                );
+                return Err(diagnostics);
+            }
+        };
+
+        // Do not continue if there are syntax errors.
+        if !diagnostics.is_empty() {
+            return Err(diagnostics);
+        }
 
         // Run the type-checking algorithm and get an unifier.
-        let (unifier, table) = pijama_tycheck::check_program(&tcx, &hir).unwrap();
+        let (unifier, types) = pijama_tycheck::check_program(&tcx, &hir)
+            .map_err(|errors| errors.into_iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+
+        if config.print_signatures {
+            for (func_id, func) in hir.functions.iter() {
+                let ty = &unifier.scheme(func_id).ty;
+
+                let mut names = VarNames::new();
+                names.add(ty);
+
+                println!("{}: {}", func.name, ty.wrap(&names));
+            }
+        }
 
         // Lower the HIR.
-        let (mir, table) = pijama_hir_lowering::lower_hir(unifier, table, hir).unwrap();
+        // FIXME: HIR lowering cannot fail yet.
+        let (mir, table) = pijama_hir_lowering::lower_hir(unifier, types, hir).unwrap();
 
         if config.codegen {
-            let obj_path = config.path.with_extension("o");
-
-            // Write the LLVM object file.
-            pijama_llvm::compile(mir, table, &obj_path).unwrap();
+            let options = pijama_llvm::Options {
+                checked: config.checked,
+                source_path: &config.path,
+                source,
+            };
+
+            if config.run && config.interpret {
+                let options =
+                    pijama_interpreter::Options::new(config.checked, &config.path, source);
+
+                return Ok(match pijama_interpreter::run(&mir, &options) {
+                    Ok(value) => Status::Returned(value.wrap(&()).to_string()),
+                    Err(error) => Status::Failed(error.to_string()),
+                });
+            }
+
+            if config.run {
+                let result = pijama_llvm::run(mir, table, options).map_err(|err| {
+                    vec![Diagnostic::new(
+                        format!("could not run the program: {}", err),
+                        Span::dummy(),
+                    )]
+                })?;
+
+                return Ok(match result {
+                    Ok(value) => Status::Returned(value.to_string()),
+                    Err(panic) => Status::Failed(panic.to_string()),
+                });
+            }
+
+            let output_path = config.path.with_extension(config.emit.extension());
+
+            // Write the file generated by LLVM.
+            pijama_llvm::compile(mir, table, &output_path, config.emit, options).map_err(
+                |err| {
+                    vec![Diagnostic::new(
+                        format!("could not write `{}`: {}", output_path.display(), err),
+                        Span::dummy(),
+                    )]
+                },
+            )?;
+
+            // Only object files can be linked.
+            if config.emit != Emit::Object {
+                return Ok(Status::Compiled);
+            }
 
             let exec_path = config.path.with_extension("out");
 
             let c_src = r#"
             #include <stdio.h>
+            #include <stdlib.h>
 
-            extern int entry();
+            extern long long entry();
+
+            void pijama_panic(const char *message) {
+                fprintf(stderr, "panic: %s\n", message);
+                exit(101);
+            }
 
             int main() {
-                int result = entry();
-                printf("%d\n", result);
+                long long result = entry();
+                printf("%lld\n", result);
                 return 0;
             }"#;
 
             let mut clang = Command::new("clang")
                 .args(&[
-                    obj_path.as_os_str(),
+                    output_path.as_os_str(),
                     OsStr::new("-o"),
                     exec_path.as_os_str(),
                     OsStr::new("-x"),
@@ -77,14 +221,28 @@ impl Compiler {
                 ])
                 .stdin(Stdio::piped())
                 .spawn()
-                .expect("Failed to spawn child process");
+                .map_err(|err| {
+                    vec![Diagnostic::new(
+                        format!("could not run clang: {}", err),
+                        Span::dummy(),
+                    )]
+                })?;
 
             let stdin = clang.stdin.as_mut().expect("Failed to open stdin");
             stdin
                 .write_all(c_src.as_bytes())
                 .expect("Failed to write to stdin");
 
-            clang.wait_with_output().expect("Failed to run clang");
+            let output = clang.wait_with_output().expect("Failed to run clang");
+
+            if !output.status.success() {
+                return Err(vec![Diagnostic::new(
+                    "could not link the executable file",
+                    Span::dummy(),
+                )]);
+            }
         }
+
+        Ok(Status::Compiled)
     }
 }
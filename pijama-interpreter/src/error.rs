@@ -0,0 +1,25 @@
+use std::fmt;
+
+/// A runtime error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A runtime check failed.
+    ///
+    /// Holds the same message reported by compiled programs, including the location of the
+    /// failed operation.
+    Panic(String),
+    /// The program exceeded the maximum number of nested calls.
+    StackOverflow,
+    /// The program exceeded the maximum number of evaluated expressions.
+    StepLimit,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic(message) => write!(f, "panic: {}", message),
+            Self::StackOverflow => write!(f, "maximum call depth exceeded"),
+            Self::StepLimit => write!(f, "maximum number of steps exceeded"),
+        }
+    }
+}
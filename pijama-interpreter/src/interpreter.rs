@@ -0,0 +1,320 @@
+use crate::{error::Error, value::Value, Options};
+
+use pijama_mir::{Atom, BinOp, Expr, ExprKind, Func, FuncId, Local, Name, Program, UnOp};
+use pijama_utils::{
+    index::{Index, IndexMap},
+    span::Span,
+};
+
+use std::sync::Arc;
+
+/// The values of the locals of a function call.
+type Frame = IndexMap<Local, Option<Value>>;
+
+/// An interpreter for programs.
+pub(crate) struct Interpreter<'a> {
+    /// The program being run.
+    program: &'a Program,
+    /// The options used to run the program.
+    options: &'a Options<'a>,
+    /// The number of calls being evaluated.
+    depth: usize,
+    /// The number of expressions evaluated so far.
+    steps: usize,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Create a new interpreter.
+    pub(crate) fn new(program: &'a Program, options: &'a Options<'a>) -> Self {
+        Self {
+            program,
+            options,
+            depth: 0,
+            steps: 0,
+        }
+    }
+
+    /// Run the program and return the value returned by its main function.
+    pub(crate) fn run(mut self) -> Result<Value, Error> {
+        self.call(FuncId::main(), None, Vec::new())
+    }
+
+    /// Call a function with the environment of a closure and a list of arguments.
+    fn call(
+        &mut self,
+        func_id: FuncId,
+        env: Option<Arc<Value>>,
+        args: Vec<Value>,
+    ) -> Result<Value, Error> {
+        if self.depth == self.options.max_depth {
+            return Err(Error::StackOverflow);
+        }
+
+        let func: &Func = self
+            .program
+            .functions
+            .get(func_id)
+            .expect("Every function should be in the program.");
+
+        // The parameters are the first locals of the function, followed by the environment.
+        let mut frame = IndexMap::from_raw(vec![None; func.locals.len()]);
+        for (index, arg) in args.into_iter().enumerate() {
+            *frame.get_mut(Local::new(index)).unwrap() = Some(arg);
+        }
+        if let (Some(local), Some(env)) = (func.env, env) {
+            *frame.get_mut(local).unwrap() = Some(Value::clone(&env));
+        }
+
+        self.depth += 1;
+        let result = self.eval(&func.body, &mut frame);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Evaluate an expression.
+    ///
+    /// The bodies of `let` bindings and the branches of conditionals are evaluated in a loop
+    /// instead of recursively, so long sequences of bindings do not use the stack of the
+    /// interpreter.
+    fn eval(&mut self, mut expr: &Expr, frame: &mut Frame) -> Result<Value, Error> {
+        loop {
+            self.steps += 1;
+            if self.steps > self.options.max_steps {
+                return Err(Error::StepLimit);
+            }
+
+            let value = match &expr.kind {
+                ExprKind::Atom(atom) => self.eval_atom(atom, frame),
+                ExprKind::Let { lhs, rhs, body } => {
+                    let value = self.eval(rhs, frame)?;
+                    *frame.get_mut(*lhs).unwrap() = Some(value);
+
+                    expr = body;
+                    continue;
+                }
+                ExprKind::Call { func, args } => {
+                    let (func, env) = match self.eval_name(func, frame) {
+                        Value::Func { func, env } => (func, env),
+                        value => panic!("Expected a function, found {:?}.", value),
+                    };
+                    let args = args.iter().map(|arg| self.eval_atom(arg, frame)).collect();
+
+                    self.call(func, env, args)?
+                }
+                ExprKind::UnaryOp { un_op, span, op } => {
+                    let op = self.eval_atom(op, frame).into_int();
+
+                    let value = match un_op {
+                        UnOp::Not => (op == 0) as i64,
+                        UnOp::Neg => self.check(
+                            op.checked_neg(),
+                            op.wrapping_neg(),
+                            "attempt to negate with overflow",
+                            *span,
+                        )?,
+                    };
+
+                    Value::Int(value)
+                }
+                ExprKind::BinaryOp {
+                    bin_op,
+                    span,
+                    left_op,
+                    right_op,
+                } => {
+                    let left_op = self.eval_atom(left_op, frame);
+                    let right_op = self.eval_atom(right_op, frame);
+
+                    self.eval_bin_op(bin_op, left_op, right_op, *span)?
+                }
+                ExprKind::Cond {
+                    cond,
+                    do_branch,
+                    else_branch,
+                } => {
+                    expr = if self.eval_atom(cond, frame).into_int() != 0 {
+                        do_branch
+                    } else {
+                        else_branch
+                    };
+                    continue;
+                }
+                ExprKind::Tuple { fields } | ExprKind::Record { fields } => Value::Tuple(
+                    fields
+                        .iter()
+                        .map(|field| self.eval_atom(field, frame))
+                        .collect(),
+                ),
+                ExprKind::Projection { tuple, index } => self
+                    .eval_atom(tuple, frame)
+                    .into_fields()
+                    .swap_remove(*index),
+                ExprKind::Construct { variant, args, .. } => Value::Adt {
+                    variant: *variant,
+                    fields: args.iter().map(|arg| self.eval_atom(arg, frame)).collect(),
+                },
+                ExprKind::Tag { value, .. } => match self.eval_atom(value, frame) {
+                    Value::Adt { variant, .. } => Value::Int(variant as i64),
+                    value => panic!("Expected an algebraic data type, found {:?}.", value),
+                },
+                ExprKind::VariantField {
+                    variant,
+                    index,
+                    value,
+                    ..
+                } => match self.eval_atom(value, frame) {
+                    Value::Adt {
+                        variant: found,
+                        mut fields,
+                    } if found == *variant => fields.swap_remove(*index),
+                    value => panic!("Expected variant {}, found {:?}.", variant, value),
+                },
+                ExprKind::Closure { func, captures } => {
+                    let captures = captures
+                        .iter()
+                        .map(|capture| self.eval_atom(capture, frame))
+                        .collect::<Vec<_>>();
+
+                    // Closures without captures do not have an environment.
+                    let env = if captures.is_empty() {
+                        None
+                    } else {
+                        Some(Arc::new(Value::Tuple(captures)))
+                    };
+
+                    Value::Func { func: *func, env }
+                }
+            };
+
+            return Ok(value);
+        }
+    }
+
+    /// Evaluate an atom.
+    fn eval_atom(&self, atom: &Atom, frame: &Frame) -> Value {
+        match atom {
+            Atom::Literal(literal) => Value::Int(literal.bits()),
+            Atom::Name(name) => self.eval_name(name, frame),
+        }
+    }
+
+    /// Evaluate a name.
+    fn eval_name(&self, name: &Name, frame: &Frame) -> Value {
+        match name {
+            Name::Local(local) => frame
+                .get(*local)
+                .cloned()
+                .flatten()
+                .expect("Every local should be bound before being used."),
+            // Functions used as values are closures without environment.
+            Name::FuncPtr(func) => Value::Func {
+                func: *func,
+                env: None,
+            },
+        }
+    }
+
+    /// Evaluate a primitive binary operation.
+    fn eval_bin_op(
+        &self,
+        bin_op: &BinOp,
+        left_op: Value,
+        right_op: Value,
+        span: Span,
+    ) -> Result<Value, Error> {
+        // Equality operators can receive values of any type.
+        match bin_op {
+            BinOp::Eq => return Ok(Value::Int((left_op == right_op) as i64)),
+            BinOp::Neq => return Ok(Value::Int((left_op != right_op) as i64)),
+            _ => (),
+        }
+
+        let left_op = left_op.into_int();
+        let right_op = right_op.into_int();
+
+        let value = match bin_op {
+            BinOp::Add => self.check(
+                left_op.checked_add(right_op),
+                left_op.wrapping_add(right_op),
+                "attempt to add with overflow",
+                span,
+            )?,
+            BinOp::Sub => self.check(
+                left_op.checked_sub(right_op),
+                left_op.wrapping_sub(right_op),
+                "attempt to subtract with overflow",
+                span,
+            )?,
+            BinOp::Mul => self.check(
+                left_op.checked_mul(right_op),
+                left_op.wrapping_mul(right_op),
+                "attempt to multiply with overflow",
+                span,
+            )?,
+            BinOp::Div => {
+                if right_op == 0 {
+                    return Err(self.panic("attempt to divide by zero", span));
+                }
+
+                self.check(
+                    left_op.checked_div(right_op),
+                    left_op.wrapping_div(right_op),
+                    "attempt to divide with overflow",
+                    span,
+                )?
+            }
+            BinOp::Rem => {
+                if right_op == 0 {
+                    return Err(self.panic(
+                        "attempt to calculate the remainder with a divisor of zero",
+                        span,
+                    ));
+                }
+
+                self.check(
+                    left_op.checked_rem(right_op),
+                    left_op.wrapping_rem(right_op),
+                    "attempt to calculate the remainder with overflow",
+                    span,
+                )?
+            }
+            BinOp::Lt => (left_op < right_op) as i64,
+            BinOp::Gt => (left_op > right_op) as i64,
+            BinOp::Lte => (left_op <= right_op) as i64,
+            BinOp::Gte => (left_op >= right_op) as i64,
+            BinOp::Eq | BinOp::Neq => unreachable!(),
+        };
+
+        Ok(Value::Int(value))
+    }
+
+    /// Use the result of a checked operation if the operations are checked, or the result of the
+    /// wrapping operation otherwise.
+    fn check(
+        &self,
+        checked: Option<i64>,
+        wrapping: i64,
+        message: &str,
+        span: Span,
+    ) -> Result<i64, Error> {
+        match checked {
+            Some(value) => Ok(value),
+            None if self.options.checked => Err(self.panic(message, span)),
+            None => Ok(wrapping),
+        }
+    }
+
+    /// Create a runtime error with a message and the location of the operation that failed.
+    fn panic(&self, message: &str, span: Span) -> Error {
+        let (line, column) = span.position(self.options.source);
+
+        Error::Panic(format!(
+            "{} at {}:{}:{}",
+            message,
+            self.options.source_path.display(),
+            line,
+            column
+        ))
+    }
+}
@@ -0,0 +1,72 @@
+//! A tree-walking interpreter for MIR.
+//!
+//! The interpreter evaluates MIR programs directly, so they can be run without LLVM. It follows
+//! the semantics of the LLVM backend and it is used as a reference to test it.
+mod error;
+mod interpreter;
+mod value;
+
+pub use error::Error;
+pub use value::Value;
+
+use interpreter::Interpreter;
+
+use pijama_mir::Program;
+
+use std::path::Path;
+
+/// The options used to run a program.
+pub struct Options<'a> {
+    /// Check for integer overflows and divisions by zero at runtime.
+    ///
+    /// If this is disabled, overflows wrap around. Dividing by zero is always an error.
+    pub checked: bool,
+    /// The path of the file being run, used to report runtime errors.
+    pub source_path: &'a Path,
+    /// The source code of the file being run, used to report runtime errors.
+    pub source: &'a str,
+    /// The maximum number of nested calls.
+    pub max_depth: usize,
+    /// The maximum number of expressions evaluated.
+    ///
+    /// This is used to stop programs that do not terminate.
+    pub max_steps: usize,
+}
+
+impl<'a> Options<'a> {
+    /// Create new options with the default limits.
+    pub fn new(checked: bool, source_path: &'a Path, source: &'a str) -> Self {
+        Self {
+            checked,
+            source_path,
+            source,
+            max_depth: 10_000,
+            max_steps: 100_000_000,
+        }
+    }
+}
+
+/// The stack size used by the interpreter for each nested call, in bytes.
+///
+/// This is about twice the size required by unoptimized builds.
+const STACK_PER_CALL: usize = 16 * 1024;
+
+/// Run a program and return the value returned by its `main` function.
+///
+/// The interpreter is recursive, so it runs in a new thread with enough stack for the maximum
+/// number of nested calls.
+pub fn run(program: &Program, options: &Options) -> Result<Value, Error> {
+    let stack_size = options
+        .max_depth
+        .saturating_add(1)
+        .saturating_mul(STACK_PER_CALL);
+
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn_scoped(scope, || Interpreter::new(program, options).run())
+            .expect("Could not spawn the interpreter's thread.")
+            .join()
+            .expect("The interpreter's thread panicked.")
+    })
+}
@@ -0,0 +1,112 @@
+use pijama_mir::FuncId;
+use pijama_utils::{index::Index, show::Show};
+
+use std::{fmt, sync::Arc};
+
+/// A runtime value.
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// An integer or a boolean.
+    ///
+    /// Booleans are represented as `0` and `1`.
+    Int(i64),
+    /// A tuple or a record.
+    Tuple(Vec<Self>),
+    /// A value of an algebraic data type.
+    Adt {
+        /// The position of the variant used to build the value.
+        variant: usize,
+        /// The values of the fields of the variant.
+        fields: Vec<Self>,
+    },
+    /// A function or a closure.
+    Func {
+        /// The ID of the function.
+        func: FuncId,
+        /// The environment of the closure, if any.
+        ///
+        /// The environment is shared by every copy of the closure.
+        env: Option<Arc<Self>>,
+    },
+}
+
+impl Value {
+    /// Get the integer stored in this value.
+    ///
+    /// This function panics if the value is not an integer, which cannot happen in well-typed
+    /// programs.
+    pub(crate) fn into_int(self) -> i64 {
+        match self {
+            Self::Int(int) => int,
+            value => panic!("Expected an integer, found {:?}.", value),
+        }
+    }
+
+    /// Get the fields of a tuple or record.
+    pub(crate) fn into_fields(self) -> Vec<Self> {
+        match self {
+            Self::Tuple(fields) => fields,
+            value => panic!("Expected a tuple, found {:?}.", value),
+        }
+    }
+}
+
+/// Values are compared in the same way as in compiled programs.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Int(left), Self::Int(right)) => left == right,
+            (Self::Tuple(left), Self::Tuple(right)) => left == right,
+            (
+                Self::Adt {
+                    variant: left_variant,
+                    fields: left_fields,
+                },
+                Self::Adt {
+                    variant: right_variant,
+                    fields: right_fields,
+                },
+            ) => left_variant == right_variant && left_fields == right_fields,
+            // Closures are equal only if they have the same code and the same environment.
+            (
+                Self::Func {
+                    func: left_func,
+                    env: left_env,
+                },
+                Self::Func {
+                    func: right_func,
+                    env: right_env,
+                },
+            ) => {
+                left_func == right_func
+                    && match (left_env, right_env) {
+                        (None, None) => true,
+                        (Some(left_env), Some(right_env)) => Arc::ptr_eq(left_env, right_env),
+                        _ => false,
+                    }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Show<()> for Value {
+    fn show(&self, ctx: &(), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(int) => write!(f, "{}", int),
+            Self::Tuple(fields) => write!(f, "({})", Show::show_sep(fields, ", ").wrap(ctx)),
+            // Values do not keep the names of the constructors, so variants are shown by their
+            // position.
+            Self::Adt { variant, fields } if fields.is_empty() => write!(f, "#{}", variant),
+            Self::Adt { variant, fields } => {
+                write!(
+                    f,
+                    "#{}({})",
+                    variant,
+                    Show::show_sep(fields, ", ").wrap(ctx)
+                )
+            }
+            Self::Func { func, .. } => write!(f, "<function {}>", func.index()),
+        }
+    }
+}
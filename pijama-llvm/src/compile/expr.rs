@@ -1,8 +1,12 @@
 use crate::{compile::Compile, compiler::FuncCompiler};
 
-use pijama_mir::{BinOp, Expr, ExprKind, UnOp};
+use pijama_mir::{BinOp, Expr, ExprKind, Name, UnOp};
+use pijama_utils::span::Span;
 
-use inkwell::{values::BasicValueEnum, IntPredicate};
+use inkwell::{
+    values::{BasicValueEnum, IntValue},
+    IntPredicate,
+};
 
 impl<'ctx> Compile<'ctx> for Expr {
     type Output = BasicValueEnum<'ctx>;
@@ -21,26 +25,74 @@ impl<'ctx> Compile<'ctx> for Expr {
                 compiler.compile(*body)
             }
             ExprKind::Call { func, args } => {
-                // First, compile the called function and turn the basic value into a pointer. This
-                // always succeeds because functions are stored as pointers and any name referring
-                // directly or indirectly to a function will be bound to a pointer.
-                let func = compiler.compile(func).into_pointer_value();
-                // Compile every argument into a basic value and collect them.
-                let args: Vec<_> = args.into_iter().map(|arg| compiler.compile(arg)).collect();
+                // First, get the code of the called function and its environment.
+                let (code, env) = match func {
+                    // Functions are called directly and they do not have an environment.
+                    Name::FuncPtr(func_id) => (
+                        compiler
+                            .get_func(func_id)
+                            .expect("Could not find function pointer inside function compiler."),
+                        compiler.null_env(),
+                    ),
+                    // Any local referring to a function is bound to a closure.
+                    Name::Local(local) => {
+                        let closure = compiler
+                            .get_local(local)
+                            .expect("Could not find local inside function compiler.")
+                            .into_struct_value();
+
+                        let builder = compiler.builder();
+                        let code = builder
+                            .build_extract_value(closure, 0, "")
+                            .unwrap()
+                            .into_pointer_value();
+                        let env = builder.build_extract_value(closure, 1, "").unwrap();
+
+                        (code, env)
+                    }
+                };
+
+                // Compile every argument into a basic value and collect them. The environment is
+                // always the first argument.
+                let args: Vec<_> = std::iter::once(env)
+                    .chain(args.into_iter().map(|arg| compiler.compile(arg)))
+                    .collect();
 
                 // Compile the actual call.
                 compiler
                     .builder()
-                    .build_call(func, &args, "")
+                    .build_call(code, &args, "")
                     // This never fails because don't have functions returning void.
                     .try_as_basic_value()
                     .unwrap_left()
             }
 
+            // Equality operators can receive values of any type.
             ExprKind::BinaryOp {
                 bin_op,
                 left_op,
                 right_op,
+                ..
+            } if matches!(bin_op, BinOp::Eq | BinOp::Neq) => {
+                // Both operands have the same type.
+                let ty = compiler.atom_ty(&left_op);
+                let left_op = compiler.compile(left_op);
+                let right_op = compiler.compile(right_op);
+
+                let eq = compiler.build_eq(&ty, left_op, right_op);
+
+                let value = match bin_op {
+                    BinOp::Neq => compiler.builder().build_not(eq, ""),
+                    _ => eq,
+                };
+
+                value.into()
+            }
+            ExprKind::BinaryOp {
+                bin_op,
+                span,
+                left_op,
+                right_op,
             } => {
                 // Compile the operands into a basic value and collect them. All of these should be
                 // integers.
@@ -50,19 +102,59 @@ impl<'ctx> Compile<'ctx> for Expr {
 
                 let builder = compiler.builder();
 
-                // FIXME: Take an stance about overflows.
                 let value = match bin_op {
+                    // If the operations are checked, overflows and divisions by zero cause a
+                    // runtime panic. Otherwise, overflows wrap around.
+                    BinOp::Add if compiler.is_checked() => build_overflowing_op(
+                        compiler,
+                        "llvm.sadd.with.overflow.i64",
+                        left_op,
+                        right_op,
+                        "attempt to add with overflow",
+                        span,
+                    ),
+                    BinOp::Sub if compiler.is_checked() => build_overflowing_op(
+                        compiler,
+                        "llvm.ssub.with.overflow.i64",
+                        left_op,
+                        right_op,
+                        "attempt to subtract with overflow",
+                        span,
+                    ),
+                    BinOp::Mul if compiler.is_checked() => build_overflowing_op(
+                        compiler,
+                        "llvm.smul.with.overflow.i64",
+                        left_op,
+                        right_op,
+                        "attempt to multiply with overflow",
+                        span,
+                    ),
+                    // Divisions by zero are always checked because they are undefined behavior.
+                    BinOp::Div => {
+                        let right_op = check_division(
+                            compiler,
+                            left_op,
+                            right_op,
+                            "attempt to divide by zero",
+                            "attempt to divide with overflow",
+                            span,
+                        );
+                        builder.build_int_signed_div(left_op, right_op, "")
+                    }
+                    BinOp::Rem => {
+                        let right_op = check_division(
+                            compiler,
+                            left_op,
+                            right_op,
+                            "attempt to calculate the remainder with a divisor of zero",
+                            "attempt to calculate the remainder with overflow",
+                            span,
+                        );
+                        builder.build_int_signed_rem(left_op, right_op, "")
+                    }
                     BinOp::Add => builder.build_int_add(left_op, right_op, ""),
                     BinOp::Sub => builder.build_int_sub(left_op, right_op, ""),
                     BinOp::Mul => builder.build_int_mul(left_op, right_op, ""),
-                    BinOp::Div => builder.build_int_signed_div(left_op, right_op, ""),
-                    BinOp::Rem => builder.build_int_signed_rem(left_op, right_op, ""),
-                    BinOp::And => builder.build_and(left_op, right_op, ""),
-                    BinOp::Or => builder.build_or(left_op, right_op, ""),
-                    BinOp::Eq => builder.build_int_compare(IntPredicate::EQ, left_op, right_op, ""),
-                    BinOp::Neq => {
-                        builder.build_int_compare(IntPredicate::NE, left_op, right_op, "")
-                    }
                     BinOp::Lt => {
                         builder.build_int_compare(IntPredicate::SLT, left_op, right_op, "")
                     }
@@ -75,17 +167,27 @@ impl<'ctx> Compile<'ctx> for Expr {
                     BinOp::Gte => {
                         builder.build_int_compare(IntPredicate::SGE, left_op, right_op, "")
                     }
+                    BinOp::Eq | BinOp::Neq => unreachable!(),
                 };
 
                 value.into()
             }
-            ExprKind::UnaryOp { un_op, op } => {
+            ExprKind::UnaryOp { un_op, span, op } => {
                 let op = compiler.compile(op).into_int_value();
 
                 let builder = compiler.builder();
 
                 let value = match un_op {
                     UnOp::Not => builder.build_not(op, ""),
+                    // Negating the smallest integer overflows.
+                    UnOp::Neg if compiler.is_checked() => build_overflowing_op(
+                        compiler,
+                        "llvm.ssub.with.overflow.i64",
+                        compiler.ctx().i64_type().const_zero(),
+                        op,
+                        "attempt to negate with overflow",
+                        span,
+                    ),
                     UnOp::Neg => builder.build_int_neg(op, ""),
                 };
 
@@ -114,12 +216,16 @@ impl<'ctx> Compile<'ctx> for Expr {
                 // Compile the do branch in the do block.
                 compiler.builder().position_at_end(do_bb);
                 let do_value = compiler.compile(*do_branch);
+                // The branch might have added new blocks, so the value comes from the current
+                // block.
+                let do_bb = compiler.builder().get_insert_block().unwrap();
                 // Jump unconditionally to the join block.
                 compiler.builder().build_unconditional_branch(join_bb);
 
                 // Compile the do branch in the else block.
                 compiler.builder().position_at_end(else_bb);
                 let else_value = compiler.compile(*else_branch);
+                let else_bb = compiler.builder().get_insert_block().unwrap();
                 // Jump unconditionally to the join block.
                 compiler.builder().build_unconditional_branch(join_bb);
 
@@ -133,7 +239,7 @@ impl<'ctx> Compile<'ctx> for Expr {
                 // This should be a basic value.
                 join_value.as_basic_value()
             }
-            ExprKind::Tuple { fields } => {
+            ExprKind::Tuple { fields } | ExprKind::Record { fields } => {
                 let ty = compiler.get_ty(self.id).unwrap().into_struct_type();
 
                 let mut value = ty.get_undef();
@@ -149,6 +255,195 @@ impl<'ctx> Compile<'ctx> for Expr {
 
                 value.into()
             }
+            ExprKind::Projection { tuple, index } => {
+                let tuple = compiler.compile(tuple).into_struct_value();
+
+                compiler
+                    .builder()
+                    .build_extract_value(tuple, index as u32, "")
+                    .unwrap()
+            }
+            ExprKind::Construct { adt, variant, args } => {
+                let layout = compiler.get_adt(adt);
+                let (adt_ty, boxed) = (layout.ty, layout.boxed);
+
+                // Boxed values are stored in the heap. Other values are built in the stack and
+                // loaded after initializing their fields.
+                let ptr = if boxed {
+                    compiler.builder().build_malloc(adt_ty, "").unwrap()
+                } else {
+                    compiler.builder().build_alloca(adt_ty, "")
+                };
+
+                let tag_ptr = compiler.builder().build_struct_gep(ptr, 0, "").unwrap();
+                let tag = compiler.ctx().i64_type().const_int(variant as u64, false);
+                compiler.builder().build_store(tag_ptr, tag);
+
+                let variant_ptr = compiler.variant_ptr(adt, variant, ptr);
+
+                for (index, arg) in args.into_iter().enumerate() {
+                    let arg = compiler.compile(arg);
+                    let field_ptr = compiler
+                        .builder()
+                        .build_struct_gep(variant_ptr, index as u32, "")
+                        .unwrap();
+                    compiler.builder().build_store(field_ptr, arg);
+                }
+
+                if boxed {
+                    ptr.into()
+                } else {
+                    compiler.builder().build_load(ptr, "")
+                }
+            }
+            ExprKind::Tag { adt, value } => {
+                let value = compiler.compile(value);
+                let ptr = compiler.adt_ptr(adt, value);
+
+                let tag_ptr = compiler.builder().build_struct_gep(ptr, 0, "").unwrap();
+                compiler.builder().build_load(tag_ptr, "")
+            }
+            ExprKind::VariantField {
+                adt,
+                variant,
+                index,
+                value,
+            } => {
+                let value = compiler.compile(value);
+                let ptr = compiler.adt_ptr(adt, value);
+
+                let variant_ptr = compiler.variant_ptr(adt, variant, ptr);
+                let field_ptr = compiler
+                    .builder()
+                    .build_struct_gep(variant_ptr, index as u32, "")
+                    .unwrap();
+                compiler.builder().build_load(field_ptr, "")
+            }
+            ExprKind::Closure { func, captures } => {
+                let env = if captures.is_empty() {
+                    compiler.null_env()
+                } else {
+                    let captures: Vec<_> = captures
+                        .into_iter()
+                        .map(|capture| compiler.compile(capture))
+                        .collect();
+                    let fields_ty: Vec<_> = captures.iter().map(|value| value.get_type()).collect();
+                    let env_ty = compiler.ctx().struct_type(&fields_ty, false);
+
+                    // The environment is stored in the heap because the closure can outlive the
+                    // current function.
+                    let env_ptr = compiler.builder().build_malloc(env_ty, "").unwrap();
+
+                    for (index, capture) in captures.into_iter().enumerate() {
+                        let field_ptr = compiler
+                            .builder()
+                            .build_struct_gep(env_ptr, index as u32, "")
+                            .unwrap();
+                        compiler.builder().build_store(field_ptr, capture);
+                    }
+
+                    compiler
+                        .builder()
+                        .build_pointer_cast(env_ptr, compiler.env_ty(), "")
+                        .into()
+                };
+
+                let code = compiler
+                    .get_func(func)
+                    .expect("Could not find function pointer inside function compiler.");
+
+                let ty = compiler.get_ty(self.id).unwrap().into_struct_type();
+                let value = compiler
+                    .builder()
+                    .build_insert_value(ty.get_undef(), code, 0, "")
+                    .unwrap()
+                    .into_struct_value();
+
+                compiler
+                    .builder()
+                    .build_insert_value(value, env, 1, "")
+                    .unwrap()
+                    .into_struct_value()
+                    .into()
+            }
         }
     }
 }
+
+/// Compile an arithmetic operation using an LLVM intrinsic that reports overflows and panic at
+/// runtime if the operation overflows.
+fn build_overflowing_op<'ctx>(
+    compiler: &FuncCompiler<'ctx, '_>,
+    intrinsic: &str,
+    left_op: IntValue<'ctx>,
+    right_op: IntValue<'ctx>,
+    message: &str,
+    span: Span,
+) -> IntValue<'ctx> {
+    let intrinsic = compiler.get_overflow_intrinsic(intrinsic);
+
+    // The intrinsic returns the result of the operation and a flag that is true if it overflowed.
+    let result = compiler
+        .builder()
+        .build_call(intrinsic, &[left_op.into(), right_op.into()], "")
+        .try_as_basic_value()
+        .unwrap_left()
+        .into_struct_value();
+
+    let value = compiler
+        .builder()
+        .build_extract_value(result, 0, "")
+        .unwrap()
+        .into_int_value();
+    let overflow = compiler
+        .builder()
+        .build_extract_value(result, 1, "")
+        .unwrap()
+        .into_int_value();
+
+    compiler.build_check(overflow, message, span);
+
+    value
+}
+
+/// Panic at runtime if a division or remainder operation divides by zero or, if the operations
+/// are checked, if it overflows.
+///
+/// Returns the divisor that must be used for the operation. Overflowing is undefined behavior in
+/// LLVM, so if the operations are not checked, the divisor is replaced by one when the operation
+/// overflows. This makes the operation wrap around: the quotient is the dividend and the
+/// remainder is zero.
+fn check_division<'ctx>(
+    compiler: &FuncCompiler<'ctx, '_>,
+    left_op: IntValue<'ctx>,
+    right_op: IntValue<'ctx>,
+    zero_message: &str,
+    overflow_message: &str,
+    span: Span,
+) -> IntValue<'ctx> {
+    let int_ty = compiler.ctx().i64_type();
+    let builder = compiler.builder();
+
+    let is_zero = builder.build_int_compare(IntPredicate::EQ, right_op, int_ty.const_zero(), "");
+    compiler.build_check(is_zero, zero_message, span);
+
+    // Dividing the smallest integer by minus one overflows.
+    let is_min = builder.build_int_compare(
+        IntPredicate::EQ,
+        left_op,
+        int_ty.const_int(i64::MIN as u64, false),
+        "",
+    );
+    let is_minus_one =
+        builder.build_int_compare(IntPredicate::EQ, right_op, int_ty.const_all_ones(), "");
+    let overflow = builder.build_and(is_min, is_minus_one, "");
+
+    if compiler.is_checked() {
+        compiler.build_check(overflow, overflow_message, span);
+        right_op
+    } else {
+        builder
+            .build_select(overflow, int_ty.const_int(1, false), right_op, "")
+            .into_int_value()
+    }
+}
@@ -13,8 +13,9 @@ impl<'ctx> Compile<'ctx> for Name {
             Name::Local(local) => compiler
                 .get_local(local)
                 .expect("Could not find local inside function compiler."),
+            // Functions used as values are closures without environment.
             Name::FuncPtr(func_id) => compiler
-                .get_func(func_id)
+                .get_func_closure(func_id)
                 .expect("Could not find function pointer inside function compiler."),
         }
     }
@@ -1,9 +1,12 @@
-use crate::compile::Compile;
+use crate::{compile::Compile, runtime, Emit, Options, Panic};
 
-use pijama_mir::{Expr, FuncId, Local, Program};
-use pijama_ty::{base::BaseTy, ty::Ty, ExprId};
+use pijama_mir::{AdtDef, Atom, Func, FuncId, Local, Name, Program};
+use pijama_ty::{base::BaseTy, ty::Ty, AdtId, ExprId};
 use pijama_tycheck::Table;
-use pijama_utils::index::IndexMap;
+use pijama_utils::{
+    index::{Index, IndexMap},
+    span::Span,
+};
 
 use inkwell::{
     basic_block::BasicBlock,
@@ -12,12 +15,12 @@ use inkwell::{
     module::{Linkage, Module},
     support::LLVMString,
     targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine},
-    types::{BasicType, BasicTypeEnum},
-    values::{BasicValueEnum, FunctionValue},
-    AddressSpace, OptimizationLevel,
+    types::{BasicType, BasicTypeEnum, FunctionType, PointerType, StructType},
+    values::{BasicValueEnum, FunctionValue, IntValue, PointerValue, StructValue},
+    AddressSpace, IntPredicate, OptimizationLevel,
 };
 
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 /// A compiler for functions.
 ///
@@ -28,7 +31,9 @@ pub(crate) struct FuncCompiler<'ctx, 'func> {
     /// The value of the function being compiled.
     func: FunctionValue<'ctx>,
     /// The locals of the function as LLVM basic values.
-    locals: IndexMap<Local, BasicValueEnum<'ctx>>,
+    locals: HashMap<Local, BasicValueEnum<'ctx>>,
+    /// The types of the locals of the function.
+    locals_ty: IndexMap<Local, Ty>,
 }
 
 impl<'ctx, 'func> FuncCompiler<'ctx, 'func> {
@@ -44,8 +49,14 @@ impl<'ctx, 'func> FuncCompiler<'ctx, 'func> {
         // function as values in it.
         //
         // This works because the parameters are always the first locals and they have the same
-        // order in the funciton's value as in the mir representation.
-        let locals = IndexMap::from_raw(func.get_params());
+        // order in the funciton's value as in the mir representation, after the environment.
+        let locals = func
+            .get_params()
+            .into_iter()
+            .skip(1)
+            .enumerate()
+            .map(|(index, value)| (Local::new(index), value))
+            .collect();
 
         // Add an entry block for the function.
         let entry_bb = compiler.ctx.append_basic_block(func, "");
@@ -56,21 +67,114 @@ impl<'ctx, 'func> FuncCompiler<'ctx, 'func> {
             compiler,
             func,
             locals,
+            locals_ty: IndexMap::new(),
+        }
+    }
+
+    /// Create a new compiler for a function that is not part of the mir program, like the ones
+    /// that compare values of algebraic data types.
+    fn synthetic(func: FunctionValue<'ctx>, compiler: &'func Compiler<'ctx>) -> Self {
+        let entry_bb = compiler.ctx.append_basic_block(func, "");
+        compiler.builder.position_at_end(entry_bb);
+
+        Self {
+            compiler,
+            func,
+            locals: HashMap::new(),
+            locals_ty: IndexMap::new(),
         }
     }
 
     /// Compile the body of the function.
     ///
-    /// This function assumes that the expression received as parameter is the body of the function
-    /// being lowered.
+    /// This function assumes that the function received as parameter is the function being
+    /// lowered.
     // FIXME: Maybe this should be called directly after initializing the compiler.
-    fn compile_func(mut self, body: Expr) {
+    fn compile_func(mut self, func: Func) {
+        // The environment of a closure is received as a pointer in the first parameter.
+        if let Some(env) = func.env {
+            let env_ty = self.compiler.lower_ty(func.locals.get(env).unwrap());
+            let env_ptr = self.func.get_first_param().unwrap().into_pointer_value();
+            let env_ptr = self.builder().build_pointer_cast(
+                env_ptr,
+                env_ty.ptr_type(AddressSpace::Generic),
+                "",
+            );
+
+            let env_value = self.builder().build_load(env_ptr, "");
+            self.insert_local(env, env_value);
+        }
+
+        // Keep the types of the locals for the operations that depend on them.
+        self.locals_ty = func.locals;
+
         // Compile the body expression into a basic value.
-        let return_value = self.compile(body);
+        let return_value = self.compile(func.body);
         // Build the return instruction with the return value.
         self.compiler.builder.build_return(Some(&return_value));
     }
 
+    /// Compile the body of the function that checks if two values of an algebraic data type are
+    /// equal.
+    ///
+    /// The values are equal if they were built with the same variant and their fields are equal.
+    fn compile_adt_eq(self, adt_id: AdtId, adt: &AdtDef) {
+        let params = self.func.get_params();
+        let left_ptr = self.adt_ptr(adt_id, params[0]);
+        let right_ptr = self.adt_ptr(adt_id, params[1]);
+
+        let builder = self.builder();
+
+        let left_tag = builder.build_struct_gep(left_ptr, 0, "").unwrap();
+        let left_tag = builder.build_load(left_tag, "").into_int_value();
+        let right_tag = builder.build_struct_gep(right_ptr, 0, "").unwrap();
+        let right_tag = builder.build_load(right_tag, "").into_int_value();
+
+        // Values built with different variants are never equal.
+        let same_tag = builder.build_int_compare(IntPredicate::EQ, left_tag, right_tag, "");
+        let switch_bb = self.add_bb();
+        let different_bb = self.add_bb();
+        builder.build_conditional_branch(same_tag, switch_bb, different_bb);
+
+        builder.position_at_end(different_bb);
+        builder.build_return(Some(&self.ctx().bool_type().const_zero()));
+
+        // Jump to the block that compares the fields of the variant used to build the values.
+        builder.position_at_end(switch_bb);
+        let cases: Vec<_> = (0..adt.variants.len())
+            .map(|index| {
+                let tag = self.ctx().i64_type().const_int(index as u64, false);
+                (tag, self.add_bb())
+            })
+            .collect();
+        builder.build_switch(left_tag, different_bb, &cases);
+
+        for ((index, variant), (_, variant_bb)) in adt.variants.iter().enumerate().zip(&cases) {
+            builder.position_at_end(*variant_bb);
+
+            let left_fields = self.variant_ptr(adt_id, index, left_ptr);
+            let right_fields = self.variant_ptr(adt_id, index, right_ptr);
+
+            let mut eq = self.ctx().bool_type().const_all_ones();
+
+            for (field, ty) in variant.fields.iter().enumerate() {
+                let left = builder
+                    .build_struct_gep(left_fields, field as u32, "")
+                    .unwrap();
+                let left = builder.build_load(left, "");
+                let right = builder
+                    .build_struct_gep(right_fields, field as u32, "")
+                    .unwrap();
+                let right = builder.build_load(right, "");
+
+                let field_eq = self.build_eq(ty, left, right);
+                eq = builder.build_and(eq, field_eq, "");
+            }
+
+            builder.build_return(Some(&eq));
+        }
+    }
+
     /// Compile a term that implements [Compile] using this compiler.
     ///
     /// Using this method is prefered over [Compile::compile_with].
@@ -80,25 +184,45 @@ impl<'ctx, 'func> FuncCompiler<'ctx, 'func> {
 
     /// Bind a basic value to a local.
     ///
-    /// This function panics if the basics values are bound in a different order as the one the
-    /// locals had inside the function's mir representation.
+    /// This function panics if the local was already bound.
     pub(crate) fn insert_local(&mut self, local: Local, value: BasicValueEnum<'ctx>) {
-        let new_local = self.locals.insert(value);
-        assert_eq!(local, new_local, "Locals are in the wrong order.");
+        let old_value = self.locals.insert(local, value);
+        assert!(old_value.is_none(), "Locals cannot be rebound.");
     }
 
     /// Get the compiled value of a local.
     pub(crate) fn get_local(&self, local: Local) -> Option<BasicValueEnum<'ctx>> {
-        self.locals.get(local).copied()
+        self.locals.get(&local).copied()
     }
 
     /// Get the compiled pointer value of a function.
-    pub(crate) fn get_func(&self, func_id: FuncId) -> Option<BasicValueEnum<'ctx>> {
+    pub(crate) fn get_func(&self, func_id: FuncId) -> Option<PointerValue<'ctx>> {
         self.compiler
             .funcs
             .get(func_id)
             // FIXME: when does this panics?.
-            .map(|value| value.as_global_value().as_pointer_value().into())
+            .map(|value| value.as_global_value().as_pointer_value())
+    }
+
+    /// Get a function as a closure without environment, so it can be used as a value.
+    pub(crate) fn get_func_closure(&self, func_id: FuncId) -> Option<BasicValueEnum<'ctx>> {
+        let code = self.get_func(func_id)?;
+
+        Some(
+            self.ctx()
+                .const_struct(&[code.into(), self.null_env()], false)
+                .into(),
+        )
+    }
+
+    /// Get the type of the environment of a closure.
+    pub(crate) fn env_ty(&self) -> PointerType<'ctx> {
+        self.compiler.env_ty()
+    }
+
+    /// Get the environment passed to the functions that do not have one.
+    pub(crate) fn null_env(&self) -> BasicValueEnum<'ctx> {
+        self.env_ty().const_null().into()
     }
 
     /// Add a new basic block at the end of the current function.
@@ -116,11 +240,224 @@ impl<'ctx, 'func> FuncCompiler<'ctx, 'func> {
         &self.compiler.builder
     }
 
+    /// Get the layout of an algebraic data type.
+    pub(crate) fn get_adt(&self, adt: AdtId) -> &AdtLayout<'ctx> {
+        self.compiler
+            .adts
+            .get(adt)
+            .expect("Every algebraic data type should have a layout by now.")
+    }
+
+    /// Get a pointer to the tagged union of a value of an algebraic data type.
+    ///
+    /// Boxed values are already pointers. Other values are stored in the stack first.
+    pub(crate) fn adt_ptr(&self, adt: AdtId, value: BasicValueEnum<'ctx>) -> PointerValue<'ctx> {
+        let layout = self.get_adt(adt);
+
+        if layout.boxed {
+            value.into_pointer_value()
+        } else {
+            let ptr = self.builder().build_alloca(layout.ty, "");
+            self.builder().build_store(ptr, value);
+            ptr
+        }
+    }
+
+    /// Get a pointer to the fields of a variant from a pointer to a tagged union.
+    pub(crate) fn variant_ptr(
+        &self,
+        adt: AdtId,
+        variant: usize,
+        ptr: PointerValue<'ctx>,
+    ) -> PointerValue<'ctx> {
+        let variant_ty = self.get_adt(adt).variants[variant];
+
+        let payload = self.builder().build_struct_gep(ptr, 1, "").unwrap();
+
+        self.builder()
+            .build_pointer_cast(payload, variant_ty.ptr_type(AddressSpace::Generic), "")
+    }
+
     pub(crate) fn get_ty(&self, expr_id: ExprId) -> Option<BasicTypeEnum<'ctx>> {
         let ty = self.compiler.table.get_ty(expr_id)?;
 
         Some(self.compiler.lower_ty(ty))
     }
+
+    /// Get the type of an atom.
+    pub(crate) fn atom_ty(&self, atom: &Atom) -> Ty {
+        match atom {
+            Atom::Literal(literal) => Ty::Base(literal.base_ty()),
+            Atom::Name(Name::Local(local)) => self
+                .locals_ty
+                .get(*local)
+                .expect("Could not find local inside function compiler.")
+                .clone(),
+            Atom::Name(Name::FuncPtr(func_id)) => self
+                .compiler
+                .funcs_ty
+                .get(*func_id)
+                .expect("Could not find function pointer inside function compiler.")
+                .clone(),
+        }
+    }
+
+    /// Build a comparison that checks if two values of a type are equal.
+    ///
+    /// Tuples and records are compared field by field and values of algebraic data types are
+    /// compared by a function generated for each type. Closures are equal only if they have the
+    /// same code and the same environment.
+    pub(crate) fn build_eq(
+        &self,
+        ty: &Ty,
+        left: BasicValueEnum<'ctx>,
+        right: BasicValueEnum<'ctx>,
+    ) -> IntValue<'ctx> {
+        let builder = self.builder();
+
+        match ty {
+            Ty::Base(_) => builder.build_int_compare(
+                IntPredicate::EQ,
+                left.into_int_value(),
+                right.into_int_value(),
+                "",
+            ),
+            Ty::Func { .. } => {
+                let (left, right) = (left.into_struct_value(), right.into_struct_value());
+                let int_ty = self.ctx().i64_type();
+
+                let mut eq = self.ctx().bool_type().const_all_ones();
+
+                // Compare the pointers to the code and to the environment.
+                for index in 0..2 {
+                    let left = builder.build_extract_value(left, index, "").unwrap();
+                    let left = builder.build_ptr_to_int(left.into_pointer_value(), int_ty, "");
+                    let right = builder.build_extract_value(right, index, "").unwrap();
+                    let right = builder.build_ptr_to_int(right.into_pointer_value(), int_ty, "");
+
+                    let field_eq = builder.build_int_compare(IntPredicate::EQ, left, right, "");
+                    eq = builder.build_and(eq, field_eq, "");
+                }
+
+                eq
+            }
+            Ty::Tuple { fields } => self.build_fields_eq(
+                fields.iter(),
+                left.into_struct_value(),
+                right.into_struct_value(),
+            ),
+            Ty::Record { fields, .. } => self.build_fields_eq(
+                fields.iter().map(|(_, ty)| ty),
+                left.into_struct_value(),
+                right.into_struct_value(),
+            ),
+            Ty::Adt { id, .. } => {
+                let eq_fn =
+                    *self.compiler.adt_eqs.get(*id).expect(
+                        "Every algebraic data type should have an equality function by now.",
+                    );
+
+                builder
+                    .build_call(eq_fn, &[left, right], "")
+                    .try_as_basic_value()
+                    .unwrap_left()
+                    .into_int_value()
+            }
+        }
+    }
+
+    /// Build a comparison that checks if all the fields of two tuples or records are equal.
+    fn build_fields_eq<'ty>(
+        &self,
+        fields: impl Iterator<Item = &'ty Ty>,
+        left: StructValue<'ctx>,
+        right: StructValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        let builder = self.builder();
+
+        let mut eq = self.ctx().bool_type().const_all_ones();
+
+        for (index, ty) in fields.enumerate() {
+            let left = builder.build_extract_value(left, index as u32, "").unwrap();
+            let right = builder
+                .build_extract_value(right, index as u32, "")
+                .unwrap();
+
+            let field_eq = self.build_eq(ty, left, right);
+            eq = builder.build_and(eq, field_eq, "");
+        }
+
+        eq
+    }
+
+    /// Check if arithmetic operations must be checked at runtime.
+    pub(crate) fn is_checked(&self) -> bool {
+        self.compiler.options.checked
+    }
+
+    /// Get an LLVM intrinsic that does an arithmetic operation over integers and reports if it
+    /// overflowed, like `llvm.sadd.with.overflow.i64`.
+    pub(crate) fn get_overflow_intrinsic(&self, name: &str) -> FunctionValue<'ctx> {
+        let module = &self.compiler.module;
+
+        module.get_function(name).unwrap_or_else(|| {
+            let int_ty = self.ctx().i64_type();
+            let return_ty = self
+                .ctx()
+                .struct_type(&[int_ty.into(), self.ctx().bool_type().into()], false);
+            let func_ty = return_ty.fn_type(&[int_ty.into(), int_ty.into()], false);
+
+            module.add_function(name, func_ty, None)
+        })
+    }
+
+    /// Build a runtime check that panics if a boolean value is true.
+    ///
+    /// The panic reports the message and the location of the span. After this, the builder is
+    /// positioned at the block where the execution continues if the check succeeds.
+    pub(crate) fn build_check(&self, failed: IntValue<'ctx>, message: &str, span: Span) {
+        let panic_bb = self.add_bb();
+        let continue_bb = self.add_bb();
+
+        self.builder()
+            .build_conditional_branch(failed, panic_bb, continue_bb);
+
+        // Call the panic function with the message and stop the execution.
+        self.builder().position_at_end(panic_bb);
+        let (line, column) = span.position(self.compiler.options.source);
+        let message = format!(
+            "{} at {}:{}:{}",
+            message,
+            self.compiler.options.source_path.display(),
+            line,
+            column
+        );
+        let message = self.builder().build_global_string_ptr(&message, "");
+        self.builder().build_call(
+            self.compiler.panic_fn,
+            &[message.as_pointer_value().into()],
+            "",
+        );
+        self.builder().build_unreachable();
+
+        self.builder().position_at_end(continue_bb);
+    }
+}
+
+/// The layout of an algebraic data type.
+///
+/// Values are represented as a tagged union: a struct with the position of the variant used to
+/// build the value and an array of integers big enough to store the fields of any variant. The
+/// fields of each variant are accessed by casting a pointer to this array.
+pub(crate) struct AdtLayout<'ctx> {
+    /// The struct type of the tagged union.
+    pub(crate) ty: StructType<'ctx>,
+    /// The struct type with the fields of each variant.
+    pub(crate) variants: Vec<StructType<'ctx>>,
+    /// Whether values of this type are stored in the heap and represented as pointers.
+    ///
+    /// Recursive types are boxed because their values could have an unbounded size.
+    pub(crate) boxed: bool,
 }
 
 /// A compiler for programs.
@@ -136,26 +473,74 @@ pub(crate) struct Compiler<'ctx> {
     builder: Builder<'ctx>,
     /// The values of each function in the program.
     funcs: IndexMap<FuncId, FunctionValue<'ctx>>,
+    /// The type of each function in the program.
+    funcs_ty: IndexMap<FuncId, Ty>,
+    /// The layout of each algebraic data type in the program.
+    adts: IndexMap<AdtId, AdtLayout<'ctx>>,
+    /// The function that checks if two values of each algebraic data type are equal.
+    adt_eqs: IndexMap<AdtId, FunctionValue<'ctx>>,
 
     table: Table,
+    /// The options used to compile the program.
+    options: Options<'ctx>,
+    /// The function called when a runtime check fails.
+    ///
+    /// This function is provided by the runtime, it receives a message and never returns.
+    panic_fn: FunctionValue<'ctx>,
 }
 
 impl<'ctx> Compiler<'ctx> {
     /// Create a new empty compiler.
-    pub(crate) fn new(ctx: &'ctx Context, table: Table) -> Self {
+    pub(crate) fn new(ctx: &'ctx Context, table: Table, options: Options<'ctx>) -> Self {
+        // We compile everything into a single module for now.
+        let module = ctx.create_module("");
+
+        // Declare the panic function of the runtime.
+        let message_ty = ctx.i8_type().ptr_type(AddressSpace::Generic);
+        let panic_ty = ctx.void_type().fn_type(&[message_ty.into()], false);
+        let panic_fn = module.add_function("pijama_panic", panic_ty, Some(Linkage::External));
+
         Self {
             ctx,
-            // We compile everything into a single module for now.
-            module: ctx.create_module(""),
+            module,
             builder: ctx.create_builder(),
             funcs: IndexMap::new(),
+            funcs_ty: IndexMap::new(),
+            adts: IndexMap::new(),
+            adt_eqs: IndexMap::new(),
             table,
+            options,
+            panic_fn,
         }
     }
 
+    /// Get the type of the environment of a closure.
+    ///
+    /// The type of the environment depends on the values captured by each closure, so it is
+    /// passed around as an opaque pointer.
+    fn env_ty(&self) -> PointerType<'ctx> {
+        self.ctx.i8_type().ptr_type(AddressSpace::Generic)
+    }
+
+    /// Lower the type of the code of a function.
+    ///
+    /// Every function receives an environment as its first parameter, even if it is not a
+    /// closure, so functions and closures can be called in the same way.
+    fn lower_fn_ty<'ty>(
+        &self,
+        params_ty: impl Iterator<Item = &'ty Ty>,
+        return_ty: &Ty,
+    ) -> FunctionType<'ctx> {
+        let params_ty: Vec<_> = std::iter::once(self.env_ty().into())
+            .chain(params_ty.map(|ty| self.lower_ty(ty)))
+            .collect();
+
+        self.lower_ty(return_ty).fn_type(&params_ty, false)
+    }
+
     /// Lower a type into a Basic LLVM type.
     ///
-    /// This can be done because function types are represented as pointer types instead.
+    /// This can be done because function types are represented as closures instead.
     fn lower_ty(&self, ty: &Ty) -> BasicTypeEnum<'ctx> {
         match ty {
             Ty::Base(base_ty) => {
@@ -169,44 +554,125 @@ impl<'ctx> Compiler<'ctx> {
                 params_ty,
                 return_ty,
             } => {
-                // Lower each type parameter.
-                let params_ty: Vec<_> = params_ty.iter().map(|ty| self.lower_ty(ty)).collect();
-
-                // Lower the return type.
-                let return_ty = self.lower_ty(return_ty.as_ref());
-
-                // Build a pointer type to a function.
-                return_ty
-                    .fn_type(&params_ty, false)
-                    // Functions are always global.
-                    .ptr_type(AddressSpace::Global)
+                // Functions are represented as closures: a pointer to the code of the function
+                // and a pointer to its environment.
+                let code_ty = self
+                    .lower_fn_ty(params_ty.iter(), return_ty)
+                    .ptr_type(AddressSpace::Generic);
+
+                self.ctx
+                    .struct_type(&[code_ty.into(), self.env_ty().into()], false)
                     .into()
             }
-            Ty::Tuple { fields } => {
-                let fields: Vec<_> = fields.iter().map(|ty| self.lower_ty(ty)).collect();
-
-                self.ctx.struct_type(&fields, false).into()
+            Ty::Tuple { fields } => self.lower_struct_ty(fields.iter()),
+            // Records are represented in the same way as tuples.
+            Ty::Record { fields, .. } => self.lower_struct_ty(fields.iter().map(|(_, ty)| ty)),
+            Ty::Adt { id, .. } => {
+                let layout = self
+                    .adts
+                    .get(*id)
+                    .expect("Every algebraic data type should have a layout by now.");
+
+                if layout.boxed {
+                    layout.ty.ptr_type(AddressSpace::Generic).into()
+                } else {
+                    layout.ty.into()
+                }
             }
         }
     }
 
-    /// Compile a mir program and write it as an object file.
-    pub(crate) fn compile(mut self, program: Program, path: &Path) -> Result<(), LLVMString> {
+    /// Lower the types of the fields of a tuple or a record into an LLVM struct type.
+    fn lower_struct_ty<'ty>(&self, fields: impl Iterator<Item = &'ty Ty>) -> BasicTypeEnum<'ctx> {
+        let fields: Vec<_> = fields.map(|ty| self.lower_ty(ty)).collect();
+
+        self.ctx.struct_type(&fields, false).into()
+    }
+
+    /// Compute the layout of each algebraic data type in the program.
+    fn lower_adts(&mut self, adts: &IndexMap<AdtId, AdtDef>) {
+        // Declare the types first because the fields of a type can have any other type.
+        for (adt_id, adt) in adts {
+            let layout = AdtLayout {
+                ty: self.ctx.opaque_struct_type(&adt.name),
+                variants: Vec::new(),
+                boxed: is_recursive(adt_id, adts),
+            };
+
+            assert_eq!(adt_id, self.adts.insert(layout), "Types are unorganized.");
+        }
+
+        for (adt_id, adt) in adts {
+            let variants: Vec<_> = adt
+                .variants
+                .iter()
+                .map(|variant| {
+                    let fields: Vec<_> =
+                        variant.fields.iter().map(|ty| self.lower_ty(ty)).collect();
+                    self.ctx.struct_type(&fields, false)
+                })
+                .collect();
+
+            let layout = self.adts.get(adt_id).unwrap();
+
+            let payload_ty = self
+                .ctx
+                .i64_type()
+                .array_type(payload_words(adt_id, adts, &self.adts));
+            layout
+                .ty
+                .set_body(&[self.ctx.i64_type().into(), payload_ty.into()], false);
+
+            self.adts.get_mut(adt_id).unwrap().variants = variants;
+        }
+    }
+
+    /// Build the function that checks if two values of each algebraic data type are equal.
+    fn lower_adt_eqs(&mut self, adts: &IndexMap<AdtId, AdtDef>) {
+        // Declare the functions first because the fields of a type can have any other type.
+        for (adt_id, adt) in adts {
+            let adt_ty = self.lower_ty(&Ty::Adt {
+                id: adt_id,
+                name: adt.name.clone(),
+            });
+            let func_ty = self.ctx.bool_type().fn_type(&[adt_ty, adt_ty], false);
+            let func = self.module.add_function("", func_ty, None);
+
+            assert_eq!(adt_id, self.adt_eqs.insert(func), "Types are unorganized.");
+        }
+
+        for (adt_id, adt) in adts {
+            let func = *self.adt_eqs.get(adt_id).unwrap();
+            FuncCompiler::synthetic(func, self).compile_adt_eq(adt_id, adt);
+        }
+    }
+
+    /// Compile a mir program into the module.
+    fn lower_program(&mut self, program: Program) {
+        self.lower_adts(&program.adts);
+        self.lower_adt_eqs(&program.adts);
+
         // Create an LLVM value for each function in the program.
         for (func_id, func) in &program.functions {
-            // Lower the types of the parameters of the function.
+            // Compute the function's type using the types of its parameters and its return type.
             let params_ty: Vec<_> = func
                 .locals
                 .iter()
                 .take(func.arity)
-                .map(|(_, ty)| self.lower_ty(ty))
+                .map(|(_, ty)| ty.clone())
                 .collect();
+            let func_ty = self.lower_fn_ty(params_ty.iter(), &func.return_ty);
 
-            // Lower the return type of the function.
-            let return_ty = self.lower_ty(&func.return_ty);
-
-            // Compute the function's type.
-            let func_ty = return_ty.fn_type(&params_ty, false);
+            // Keep the type of the function to know the type of the atoms that use it.
+            let ty = Ty::Func {
+                params_ty,
+                return_ty: Box::new(func.return_ty.clone()),
+            };
+            assert_eq!(
+                func_id,
+                self.funcs_ty.insert(ty),
+                "Functions are unorganized."
+            );
 
             // Add a new value with the function's type.
             let func_value = self.module.add_function("", func_ty, None);
@@ -221,7 +687,7 @@ impl<'ctx> Compiler<'ctx> {
 
         // Compile each function.
         for (func_id, func) in program.functions {
-            FuncCompiler::new(func_id, &self).compile_func(func.body);
+            FuncCompiler::new(func_id, &self).compile_func(func);
         }
 
         // Get the value for the main function.
@@ -238,10 +704,27 @@ impl<'ctx> Compiler<'ctx> {
         self.builder.position_at_end(entry_bb);
         let result = self
             .builder
-            .build_call(main_fn, &[], "")
+            .build_call(main_fn, &[self.env_ty().const_null().into()], "")
             .try_as_basic_value()
-            .unwrap_left();
+            .unwrap_left()
+            .into_int_value();
+        // The type checker only allows main to return integers or booleans, which are extended
+        // to integers.
+        let result = self
+            .builder
+            .build_int_z_extend_or_bit_cast(result, self.ctx.i64_type(), "");
         self.builder.build_return(Some(&result));
+    }
+
+    /// Compile a mir program and write it as a file of the given kind.
+    pub(crate) fn compile(
+        mut self,
+        program: Program,
+        path: &Path,
+        emit: Emit,
+    ) -> Result<(), String> {
+        self.lower_program(program);
+        self.module.verify().map_err(|err| err.to_string())?;
 
         Target::initialize_all(&InitializationConfig::default());
         let target_triple = TargetMachine::get_default_triple();
@@ -258,7 +741,125 @@ impl<'ctx> Compiler<'ctx> {
             )
             .unwrap();
 
-        // Write the object file.
-        target_machine.write_to_file(&self.module, FileType::Object, path)
+        // Store the target in the module so the emitted IR can be compiled by other tools.
+        self.module.set_triple(&target_triple);
+        self.module
+            .set_data_layout(&target_machine.get_target_data().get_data_layout());
+
+        let result = match emit {
+            Emit::Object => target_machine.write_to_file(&self.module, FileType::Object, path),
+            Emit::Assembly => target_machine.write_to_file(&self.module, FileType::Assembly, path),
+            Emit::Ir => self.module.print_to_file(path),
+            // Writing bitcode does not return an error message.
+            Emit::Bitcode if self.module.write_bitcode_to_path(path) => Ok(()),
+            Emit::Bitcode => return Err("could not write the bitcode".to_owned()),
+        };
+
+        result.map_err(|err| err.to_string())
+    }
+
+    /// Compile a mir program and run it just in time, returning the value returned by its main
+    /// function or the runtime check that failed.
+    pub(crate) fn run(mut self, program: Program) -> Result<Result<i64, Panic>, LLVMString> {
+        self.lower_program(program);
+        self.module.verify()?;
+
+        Target::initialize_native(&InitializationConfig::default())
+            .expect("Could not initialize the native target.");
+
+        let engine = self
+            .module
+            .create_jit_execution_engine(OptimizationLevel::Aggressive)?;
+
+        // There is no C runtime linked with the program, so the panic function is provided by
+        // the compiler instead.
+        engine.add_global_mapping(&self.panic_fn, runtime::pijama_panic as usize);
+
+        // This is safe because `entry` does not receive any parameters and returns an integer.
+        let entry = unsafe { engine.get_function::<unsafe extern "C" fn() -> i64>("entry") }
+            .expect("The `entry` function should have been compiled.");
+
+        // This is safe because the engine keeps the code of `entry` alive until the program
+        // finishes.
+        Ok(unsafe { runtime::run_entry(entry.as_raw()) })
+    }
+}
+
+/// Check if an algebraic data type can contain values of itself.
+fn is_recursive(adt_id: AdtId, adts: &IndexMap<AdtId, AdtDef>) -> bool {
+    let mut visited = Vec::new();
+    let mut pending = vec![adt_id];
+
+    while let Some(id) = pending.pop() {
+        for variant in &adts.get(id).unwrap().variants {
+            for ty in &variant.fields {
+                let mut found = Vec::new();
+                collect_adts(ty, &mut found);
+
+                for found_id in found {
+                    if found_id == adt_id {
+                        return true;
+                    }
+
+                    if !visited.contains(&found_id) {
+                        visited.push(found_id);
+                        pending.push(found_id);
+                    }
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Collect the algebraic data types stored inside the values of a type.
+fn collect_adts(ty: &Ty, adts: &mut Vec<AdtId>) {
+    match ty {
+        // The environments of closures are pointers, so they do not store any values.
+        Ty::Base(_) | Ty::Func { .. } => (),
+        Ty::Tuple { fields } => fields.iter().for_each(|ty| collect_adts(ty, adts)),
+        Ty::Record { fields, .. } => fields.iter().for_each(|(_, ty)| collect_adts(ty, adts)),
+        Ty::Adt { id, .. } => adts.push(*id),
+    }
+}
+
+/// Compute the number of 64-bit words required to store the fields of any variant of an algebraic
+/// data type.
+fn payload_words(
+    adt_id: AdtId,
+    adts: &IndexMap<AdtId, AdtDef>,
+    layouts: &IndexMap<AdtId, AdtLayout<'_>>,
+) -> u32 {
+    adts.get(adt_id)
+        .unwrap()
+        .variants
+        .iter()
+        .map(|variant| {
+            variant
+                .fields
+                .iter()
+                .map(|ty| words(ty, adts, layouts))
+                .sum()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Compute an upper bound for the number of 64-bit words required to store a value of a type.
+///
+/// Every field takes at least one word, so this is never smaller than the actual size of the
+/// type.
+fn words(ty: &Ty, adts: &IndexMap<AdtId, AdtDef>, layouts: &IndexMap<AdtId, AdtLayout<'_>>) -> u32 {
+    match ty {
+        Ty::Base(_) => 1,
+        // Closures are a pointer to their code and a pointer to their environment.
+        Ty::Func { .. } => 2,
+        Ty::Tuple { fields } => fields.iter().map(|ty| words(ty, adts, layouts)).sum(),
+        Ty::Record { fields, .. } => fields.iter().map(|(_, ty)| words(ty, adts, layouts)).sum(),
+        // Boxed types are pointers. Types that are not boxed cannot contain themselves, so this
+        // always terminates.
+        Ty::Adt { id, .. } if layouts.get(*id).unwrap().boxed => 1,
+        Ty::Adt { id, .. } => 1 + payload_words(*id, adts, layouts),
     }
 }
@@ -1,5 +1,6 @@
 mod compile;
 mod compiler;
+mod runtime;
 
 use compiler::Compiler;
 
@@ -8,9 +9,77 @@ use pijama_tycheck::Table;
 
 use inkwell::{context::Context, support::LLVMString};
 
-use std::path::Path;
+use std::{fmt, path::Path};
 
-pub fn compile(program: Program, table: Table, path: &Path) -> Result<(), LLVMString> {
+/// The options used to compile a program.
+pub struct Options<'a> {
+    /// Check for integer overflows and divisions by zero at runtime.
+    ///
+    /// If this is disabled, overflows wrap around. Dividing by zero always panics.
+    pub checked: bool,
+    /// The path of the file being compiled, used to report runtime errors.
+    pub source_path: &'a Path,
+    /// The source code of the file being compiled, used to report runtime errors.
+    pub source: &'a str,
+}
+
+/// The kind of file written when compiling a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// An object file.
+    Object,
+    /// A textual assembly file.
+    Assembly,
+    /// A file with LLVM bitcode.
+    Bitcode,
+    /// A file with textual LLVM IR.
+    Ir,
+}
+
+impl Emit {
+    /// The extension of the files of this kind.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Emit::Object => "o",
+            Emit::Assembly => "s",
+            Emit::Bitcode => "bc",
+            Emit::Ir => "ll",
+        }
+    }
+}
+
+/// Compile a program and write it as a file of the given kind.
+pub fn compile(
+    program: Program,
+    table: Table,
+    path: &Path,
+    emit: Emit,
+    options: Options,
+) -> Result<(), String> {
+    let context = Context::create();
+    Compiler::new(&context, table, options).compile(program, path, emit)
+}
+
+/// A runtime check that failed while running a program just in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panic {
+    /// The message of the check, including the location of the failed operation.
+    pub message: String,
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "panic: {}", self.message)
+    }
+}
+
+/// Compile a program and run it just in time, returning the value returned by its `main`
+/// function or the runtime check that failed.
+pub fn run(
+    program: Program,
+    table: Table,
+    options: Options,
+) -> Result<Result<i64, Panic>, LLVMString> {
     let context = Context::create();
-    Compiler::new(&context, table).compile(program, path)
+    Compiler::new(&context, table, options).run(program)
 }
@@ -0,0 +1,75 @@
+//! The runtime functions used by programs that are run just in time.
+//!
+//! Object files are linked with a C implementation of these functions instead.
+
+use crate::Panic;
+
+use std::{
+    cell::RefCell,
+    ffi::CStr,
+    os::raw::c_char,
+    sync::mpsc::{channel, Sender},
+    thread,
+};
+
+/// The size of the stack of the thread that runs a program, which is the usual size of the stack
+/// of the main thread.
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+thread_local! {
+    /// The channel used to report the result of the program run by the current thread.
+    static RESULT: RefCell<Option<Sender<Result<i64, Panic>>>> = RefCell::new(None);
+}
+
+/// Run the `entry` function of a program in a new thread and return its result.
+///
+/// If a runtime check fails, the thread stops running the program and the message of the check
+/// is returned instead.
+///
+/// # Safety
+///
+/// The `entry` function must be valid until this function returns.
+pub(crate) unsafe fn run_entry(entry: unsafe extern "C" fn() -> i64) -> Result<i64, Panic> {
+    let (sender, receiver) = channel();
+
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            RESULT.with(|result| *result.borrow_mut() = Some(sender.clone()));
+            // This is safe because the caller keeps the code of the program alive until the
+            // result is received.
+            let value = unsafe { entry() };
+            // The receiver is only dropped after receiving a result.
+            sender.send(Ok(value)).unwrap();
+        })
+        .expect("Could not spawn the thread to run the program.");
+
+    receiver
+        .recv()
+        .expect("The program should report a result before its thread stops.")
+}
+
+/// Report that a runtime check failed and stop the program.
+pub(crate) extern "C" fn pijama_panic(message: *const c_char) -> ! {
+    // This is safe because the message is always a null-terminated string built by the
+    // compiler.
+    let message = unsafe { CStr::from_ptr(message) };
+    let panic = Panic {
+        message: message.to_string_lossy().into_owned(),
+    };
+
+    RESULT.with(|result| {
+        result
+            .borrow()
+            .as_ref()
+            .expect("Programs should only run inside `run_entry`.")
+            .send(Err(panic))
+            .unwrap()
+    });
+
+    // The program cannot continue and its code cannot be unwound, so this thread sleeps forever
+    // instead of returning to it.
+    loop {
+        thread::park();
+    }
+}
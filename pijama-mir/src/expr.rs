@@ -1,10 +1,12 @@
 use crate::{
     atom::Atom,
+    func::FuncId,
     name::{Local, Name},
     prim_op::{BinOp, UnOp},
 };
 
-use pijama_ty::ExprId;
+use pijama_ty::{AdtId, ExprId};
+use pijama_utils::span::Span;
 
 /// An expression.
 ///
@@ -41,6 +43,7 @@ pub enum ExprKind {
         /// The name of the called function.
         ///
         /// This can be a local because of expressions like: `(if cond do f else g end)(arg)`.
+        /// Locals are called as closures, with the environment they were built with.
         func: Name,
         /// The arguments of the call.
         ///
@@ -52,6 +55,8 @@ pub enum ExprKind {
     UnaryOp {
         /// The primitive unary operator.
         un_op: UnOp,
+        /// The location of the operation in the source code, used to report runtime errors.
+        span: Span,
         /// The operand of the operation.
         ///
         /// Operands must be atoms to avoid nesting expressions. Which means that any non-atomic
@@ -60,8 +65,10 @@ pub enum ExprKind {
     },
     /// A primitive binary operation.
     BinaryOp {
-        /// The primitive unary operator.
+        /// The primitive binary operator.
         bin_op: BinOp,
+        /// The location of the operation in the source code, used to report runtime errors.
+        span: Span,
         /// The left-hand side operand of the operation.
         ///
         /// Operands must be atoms to avoid nesting expressions. Which means that any non-atomic
@@ -84,4 +91,56 @@ pub enum ExprKind {
     },
     /// A tuple expression,
     Tuple { fields: Vec<Atom> },
+    /// A record.
+    ///
+    /// Records have the same representation as tuples, so their fields are accessed using
+    /// projections.
+    Record {
+        /// The values of the fields, in the order they were declared.
+        fields: Vec<Atom>,
+    },
+    /// A tuple or record projection.
+    Projection {
+        /// The projected tuple or record.
+        tuple: Atom,
+        /// The index of the field.
+        index: usize,
+    },
+    /// A value of an algebraic data type built with the constructor of one of its variants.
+    Construct {
+        /// The ID of the type.
+        adt: AdtId,
+        /// The position of the variant in the type's definition.
+        variant: usize,
+        /// The values of the fields of the variant.
+        args: Vec<Atom>,
+    },
+    /// The position of the variant used to build a value of an algebraic data type, as an
+    /// integer.
+    Tag {
+        /// The ID of the type.
+        adt: AdtId,
+        /// The value of the type.
+        value: Atom,
+    },
+    /// A field of a value of an algebraic data type.
+    ///
+    /// The value must have been built with the constructor of the variant.
+    VariantField {
+        /// The ID of the type.
+        adt: AdtId,
+        /// The position of the variant in the type's definition.
+        variant: usize,
+        /// The index of the field.
+        index: usize,
+        /// The value of the type.
+        value: Atom,
+    },
+    /// A closure built from a function and the values of the locals it captures.
+    Closure {
+        /// The ID of the lifted function.
+        func: FuncId,
+        /// The values of the captured locals, stored in the closure's environment.
+        captures: Vec<Atom>,
+    },
 }
@@ -18,12 +18,18 @@ impl FuncId {
 
 /// A function.
 ///
-/// In this IR, functions are C-like, which means they are not closures and all of them are
-/// globally defined. Each function has a globally unique [FuncId] assigned to it.
+/// In this IR, all functions are globally defined and each one of them has a globally unique
+/// [FuncId] assigned to it. Anonymous functions are lifted into closures: functions that receive
+/// the values of the locals they capture in an environment.
 #[derive(Debug)]
 pub struct Func {
     /// The number of parameters of the function.
     pub arity: usize,
+    /// The local bound to the environment of the function if it is a closure.
+    ///
+    /// The environment is a tuple with the values of the captured locals and this local is the
+    /// one right after the parameters.
+    pub env: Option<Local>,
     /// The local values of the function with their types.
     ///
     /// The first `arity` locals correspond to the function's parameters.
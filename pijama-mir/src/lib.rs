@@ -1,4 +1,5 @@
 /// MIR is Pijama's last IR before compiling to LLVM.
+mod adt;
 mod atom;
 mod expr;
 mod func;
@@ -7,6 +8,7 @@ mod name;
 mod prim_op;
 mod program;
 
+pub use adt::{AdtDef, Variant};
 pub use atom::Atom;
 pub use expr::{Expr, ExprKind};
 pub use func::{Func, FuncId};
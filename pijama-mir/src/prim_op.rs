@@ -20,10 +20,6 @@ pub enum BinOp {
     Div,
     /// The arithmetic remainder operator.
     Rem,
-    /// The logical conjunction operator.
-    And,
-    /// The logical disjunction operator.
-    Or,
     /// The equality operator.
     Eq,
     /// The "not equal to" operator.
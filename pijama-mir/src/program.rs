@@ -1,5 +1,9 @@
-use crate::func::{Func, FuncId};
+use crate::{
+    adt::AdtDef,
+    func::{Func, FuncId},
+};
 
+use pijama_ty::AdtId;
 use pijama_utils::index::IndexMap;
 
 /// A program.
@@ -7,4 +11,6 @@ use pijama_utils::index::IndexMap;
 pub struct Program {
     /// The functions of the program.
     pub functions: IndexMap<FuncId, Func>,
+    /// The algebraic data types of the program.
+    pub adts: IndexMap<AdtId, AdtDef>,
 }
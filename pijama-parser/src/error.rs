@@ -4,7 +4,7 @@ use pijama_utils::{span::Span, spanned_type};
 
 use lalrpop_util::ParseError as LalrpopError;
 
-pub type ParseResult<'source, T> = Result<T, ParseError<'source>>;
+use std::fmt;
 
 spanned_type!(pub ParseError<'source>, ParseErrorKind);
 
@@ -22,6 +22,37 @@ pub enum ParseErrorKind<'source> {
     },
     /// The source input has an invalid token.
     InvalidToken,
+    /// The source input could not be split into tokens.
+    Lexer(LexerErrorKind),
+    /// A tuple projection with an index that is not a non-negative integer.
+    InvalidFieldIndex(i64),
+}
+
+impl<'source> fmt::Display for ParseErrorKind<'source> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEOF { expected } => {
+                write!(f, "unexpected end of file")?;
+                fmt_expected(expected, f)
+            }
+            Self::UnexpectedToken { expected, found } => {
+                write!(f, "unexpected token `{}`", found)?;
+                fmt_expected(expected, f)
+            }
+            Self::InvalidToken => write!(f, "invalid token"),
+            Self::Lexer(kind) => write!(f, "{}", kind),
+            Self::InvalidFieldIndex(index) => write!(f, "invalid field index `{}`", index),
+        }
+    }
+}
+
+/// Write the list of expected tokens reported by LALRPOP, if any.
+fn fmt_expected(expected: &[String], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match expected {
+        [] => Ok(()),
+        [token] => write!(f, ", expected {}", token),
+        tokens => write!(f, ", expected one of {}", tokens.join(", ")),
+    }
 }
 
 impl<'source> From<LalrpopError<usize, Token<'source>, LexerError>> for ParseError<'source> {
@@ -51,15 +82,71 @@ impl<'source> From<LalrpopError<usize, Token<'source>, LexerError>> for ParseErr
                 },
                 span: Span::new(start, end),
             },
-            // Lexing errors are impossible here.
-            LalrpopError::User { .. } => unreachable!(),
+            LalrpopError::User { error } => ParseError::from(error),
         }
     }
 }
 
+impl<'source> From<LexerError> for ParseError<'source> {
+    fn from(error: LexerError) -> Self {
+        ParseError {
+            kind: ParseErrorKind::Lexer(error.kind),
+            span: error.span,
+        }
+    }
+}
+
+impl<'source> ParseErrorKind<'source> {
+    /// Consume the current kind to return an error.
+    pub(crate) fn into_err(self, span: Span) -> ParseError<'source> {
+        ParseError { kind: self, span }
+    }
+}
+
+impl<'source> ParseError<'source> {
+    /// Check if this error was caused by a [Token::Error].
+    ///
+    /// Those errors are reported by the lexer with a more precise reason.
+    pub(crate) fn is_lexer_error(&self) -> bool {
+        matches!(
+            self.kind,
+            ParseErrorKind::UnexpectedToken {
+                found: Token::Error,
+                ..
+            }
+        )
+    }
+}
+
+spanned_type!(pub LexerError, LexerErrorKind);
+
 /// A Lexing error.
 ///
-/// This error type is required by LALRPOP but it is empty the lexer cannot fail, if there is an
-/// invalid input, Logos will return a [Token::Error] instead.
+/// Logos returns a [Token::Error] for any invalid input, this type represents the reasons why the
+/// input is invalid.
 #[derive(Debug)]
-pub enum LexerError {}
+pub enum LexerErrorKind {
+    /// A character that cannot start any token.
+    UnknownChar(char),
+    /// An integer literal that does not fit in 64 bits.
+    IntegerOutOfRange,
+    /// A block comment was not closed before the end of the file.
+    UnterminatedComment,
+}
+
+impl fmt::Display for LexerErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownChar(c) => write!(f, "unknown character `{}`", c.escape_default()),
+            Self::IntegerOutOfRange => write!(f, "integer literal too large"),
+            Self::UnterminatedComment => write!(f, "unterminated block comment"),
+        }
+    }
+}
+
+impl LexerErrorKind {
+    /// Consume the current kind to return an error.
+    pub(crate) fn into_err(self, span: Span) -> LexerError {
+        LexerError { kind: self, span }
+    }
+}
@@ -6,10 +6,11 @@
 pub mod error;
 pub mod token;
 
-use error::{ParseError, ParseResult};
+use error::{LexerError, LexerErrorKind, ParseError};
 use token::Token;
 
 use pijama_ast::Program;
+use pijama_utils::span::Span;
 
 use lalrpop_util::lalrpop_mod;
 use logos::Logos;
@@ -17,14 +18,111 @@ use logos::Logos;
 lalrpop_mod!(parser);
 
 /// Parse a string slice into an AST.
-pub fn parse<'source>(source: &'source str) -> ParseResult<'source, Program<'source>> {
-    // Create a new lexer and map it into an iterator that LALRPOP can handle.
-    let lexer = Token::lexer(source)
-        .spanned()
-        .map(|(token, span)| Ok((span.start, token, span.end)));
-
-    // FIXME: Map LALRPOP errors into something we can display.
-    parser::ProgramParser::new()
-        .parse(source, lexer)
-        .map_err(ParseError::from)
+///
+/// The parser tries to recover from syntax errors, so this function returns every error found
+/// alongside a partial AST where the expressions that could not be parsed are replaced by
+/// [pijama_ast::ExprKind::Error] nodes.
+pub fn parse<'source>(source: &'source str) -> (Program<'source>, Vec<ParseError<'source>>) {
+    let mut lexer_errors = Vec::new();
+
+    let tokens = Token::lexer(source).spanned().collect::<Vec<_>>();
+
+    // Doc comments are only kept if they document an item, otherwise they are skipped like
+    // regular comments. The tokens are traversed backwards so each doc comment knows if the
+    // tokens following it start an item without scanning them again.
+    let mut keep = vec![true; tokens.len()];
+    let mut documents_item = false;
+    let mut next = None;
+
+    for (index, (token, _)) in tokens.iter().enumerate().rev() {
+        match token {
+            Token::DocComment(_) => keep[index] = documents_item,
+            token => {
+                documents_item = starts_item(token, next);
+                next = Some(token);
+            }
+        }
+    }
+
+    // Map the tokens into an iterator that LALRPOP can handle.
+    let lexer = tokens
+        .into_iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|((token, span), _)| {
+            // Record the lexing errors but still pass the error tokens to the parser so it can
+            // recover from them.
+            if let Token::Error = token {
+                lexer_errors.push(lexer_error(&source[span.clone()], span.start));
+            }
+
+            Ok::<_, LexerError>((span.start, token, span.end))
+        });
+
+    let mut recovered = Vec::new();
+    let mut errors = Vec::new();
+    let result = parser::ProgramParser::new().parse(source, &mut recovered, &mut errors, lexer);
+
+    // Collect the errors the parser recovered from. The errors caused by error tokens are
+    // replaced by the lexing errors.
+    errors.extend(
+        recovered
+            .into_iter()
+            .map(|recovery| ParseError::from(recovery.error))
+            .filter(|error| !error.is_lexer_error()),
+    );
+
+    let program = match result {
+        Ok(program) => program,
+        // If the parser could not recover, there is no AST to return.
+        Err(error) => {
+            let error = ParseError::from(error);
+            if !error.is_lexer_error() {
+                errors.push(error);
+            }
+            Program {
+                functions: Vec::new(),
+                adts: Vec::new(),
+                structs: Vec::new(),
+                aliases: Vec::new(),
+            }
+        }
+    };
+
+    errors.extend(lexer_errors.into_iter().map(ParseError::from));
+    // Report the errors in the same order as they appear in the source code.
+    errors.sort_by_key(|error| error.span.start);
+
+    (program, errors)
+}
+
+/// Check if a token starts an item, given the token after it without counting doc comments.
+fn starts_item(token: &Token, next: Option<&Token>) -> bool {
+    match (token, next) {
+        // Anonymous functions cannot be documented.
+        (Token::Fn, Some(Token::Ident(_))) => true,
+        (Token::Type, _) | (Token::Struct, _) => true,
+        _ => false,
+    }
+}
+
+/// Find out why the lexer returned a [Token::Error] for a slice of the source code starting at
+/// `start`.
+fn lexer_error(slice: &str, start: usize) -> LexerError {
+    let span = Span::new(start, start + slice.len());
+
+    // Unterminated block comments are the only errors that consume the `/*` token.
+    if slice.starts_with("/*") {
+        return LexerErrorKind::UnterminatedComment.into_err(span.with_end(start + 2));
+    }
+
+    // The integer regex only fails if the literal does not fit in an `i64`.
+    let digits = slice.strip_prefix('-').unwrap_or(slice);
+    if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return LexerErrorKind::IntegerOutOfRange.into_err(span);
+    }
+
+    // Otherwise, the slice starts with a character that cannot start any token.
+    let c = slice.chars().next().expect("Error tokens cannot be empty.");
+    LexerErrorKind::UnknownChar(c).into_err(span.with_end(start + c.len_utf8()))
 }
@@ -1,4 +1,6 @@
-use logos::Logos;
+use logos::{Filter, Lexer, Logos};
+
+use std::fmt;
 
 /// A token for Pijama's syntax.
 #[derive(Logos, Debug, Clone)]
@@ -38,6 +40,21 @@ pub enum Token<'source> {
     /// The `end` token.
     #[token("end")]
     End,
+    /// The `match` token.
+    #[token("match")]
+    Match,
+    /// The `type` token.
+    #[token("type")]
+    Type,
+    /// The `struct` token.
+    #[token("struct")]
+    Struct,
+    /// The `with` token.
+    #[token("with")]
+    With,
+    /// The `_` token.
+    #[token("_")]
+    Underscore,
     /// The `+` token.
     #[token("+")]
     Add,
@@ -59,6 +76,9 @@ pub enum Token<'source> {
     /// The `||` token.
     #[token("||")]
     Or,
+    /// The `|` token.
+    #[token("|")]
+    Pipe,
     /// The `!` token.
     #[token("!")]
     Not,
@@ -89,6 +109,9 @@ pub enum Token<'source> {
     /// The `,` token.
     #[token(",")]
     Comma,
+    /// The `.` token.
+    #[token(".")]
+    Dot,
     /// The `->` token.
     #[token("->")]
     Arrow,
@@ -98,10 +121,116 @@ pub enum Token<'source> {
     /// The `)` token.
     #[token(")")]
     CloseParen,
+    /// The `{` token.
+    #[token("{")]
+    OpenBrace,
+    /// The `}` token.
+    #[token("}")]
+    CloseBrace,
+    /// The `[` token.
+    #[token("[")]
+    OpenBracket,
+    /// The `]` token.
+    #[token("]")]
+    CloseBracket,
+    /// A documentation comment.
+    ///
+    /// Holds the text of the comment after the leading `///`.
+    #[regex("///[^\n]*", |lex| &lex.slice()[3..])]
+    DocComment(&'source str),
     /// A placeholder token for errors.
     ///
-    /// This variant is required by Logos and it is the only way to handle lexing errors.
+    /// This variant is required by Logos and it is the only way to handle lexing errors. Whitespace
+    /// and regular comments are also skipped here.
     #[error]
     #[regex(r"[ \t\n\f]+", logos::skip)]
+    #[regex("//[^\n]*", logos::skip)]
+    #[token("/*", block_comment)]
     Error,
 }
+
+/// Skip a block comment, taking into account that block comments can be nested.
+///
+/// If the comment is not terminated, the rest of the input is consumed and an error token is
+/// emitted.
+fn block_comment<'source>(lex: &mut Lexer<'source, Token<'source>>) -> Filter<()> {
+    let remainder = lex.remainder();
+    let bytes = remainder.as_bytes();
+
+    // The opening `/*` was already consumed.
+    let mut depth = 1;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match &bytes[pos..] {
+            [b'/', b'*', ..] => {
+                depth += 1;
+                pos += 2;
+            }
+            [b'*', b'/', ..] => {
+                depth -= 1;
+                pos += 2;
+
+                if depth == 0 {
+                    lex.bump(pos);
+                    return Filter::Skip;
+                }
+            }
+            _ => pos += 1,
+        }
+    }
+
+    lex.bump(remainder.len());
+    Filter::Emit(())
+}
+
+impl<'source> fmt::Display for Token<'source> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(integer) => write!(f, "{}", integer),
+            Self::Ident(ident) => write!(f, "{}", ident),
+            Self::True => write!(f, "true"),
+            Self::False => write!(f, "false"),
+            Self::Let => write!(f, "let"),
+            Self::Semicolon => write!(f, ";"),
+            Self::Fn => write!(f, "fn"),
+            Self::If => write!(f, "if"),
+            Self::Do => write!(f, "do"),
+            Self::Else => write!(f, "else"),
+            Self::End => write!(f, "end"),
+            Self::Match => write!(f, "match"),
+            Self::Type => write!(f, "type"),
+            Self::Struct => write!(f, "struct"),
+            Self::With => write!(f, "with"),
+            Self::Underscore => write!(f, "_"),
+            Self::Add => write!(f, "+"),
+            Self::Sub => write!(f, "-"),
+            Self::Mul => write!(f, "*"),
+            Self::Div => write!(f, "/"),
+            Self::Rem => write!(f, "%"),
+            Self::And => write!(f, "&&"),
+            Self::Or => write!(f, "||"),
+            Self::Pipe => write!(f, "|"),
+            Self::Not => write!(f, "!"),
+            Self::Eq => write!(f, "=="),
+            Self::Neq => write!(f, "!="),
+            Self::Gt => write!(f, ">"),
+            Self::Lt => write!(f, "<"),
+            Self::Gte => write!(f, ">="),
+            Self::Lte => write!(f, "<="),
+            Self::Assign => write!(f, "="),
+            Self::Colon => write!(f, ":"),
+            Self::Comma => write!(f, ","),
+            Self::Dot => write!(f, "."),
+            Self::Arrow => write!(f, "->"),
+            Self::OpenParen => write!(f, "("),
+            Self::CloseParen => write!(f, ")"),
+            Self::OpenBrace => write!(f, "{{"),
+            Self::CloseBrace => write!(f, "}}"),
+            Self::OpenBracket => write!(f, "["),
+            Self::CloseBracket => write!(f, "]"),
+            Self::DocComment(text) => write!(f, "///{}", text),
+            Self::Error => write!(f, "invalid token"),
+        }
+    }
+}
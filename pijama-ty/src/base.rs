@@ -3,7 +3,7 @@
 use pijama_utils::show::Show;
 
 /// A base type.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum BaseTy {
     /// The boolean type.
     Bool,
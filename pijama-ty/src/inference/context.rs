@@ -26,8 +26,15 @@ impl TyContext {
     ///
     /// The type returned is guaranteed to be different from any other type created by this context.
     pub fn new_ty(&self) -> Ty {
-        let var = self.ty_var_gen.generate();
-        Ty::Var(var)
+        Ty::Var(self.new_var())
+    }
+
+    /// Create a new type variable.
+    ///
+    /// The variable returned is guaranteed to be different from any other variable created by
+    /// this context.
+    pub fn new_var(&self) -> TyVar {
+        self.ty_var_gen.generate()
     }
 
     pub fn new_expr_id(&self) -> ExprId {
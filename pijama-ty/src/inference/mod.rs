@@ -1,6 +1,8 @@
 //! Types for inference.
 mod context;
+mod names;
 mod ty;
 
 pub use context::TyContext;
+pub use names::VarNames;
 pub use ty::{Ty, TyVar};
@@ -0,0 +1,45 @@
+use crate::inference::ty::{Ty, TyVar};
+
+use pijama_utils::show::Show;
+
+/// A printing context that gives human-friendly names to type variables.
+///
+/// Variables are named `'a`, `'b`, ..., `'z`, `'a1`, `'b1`, ... following the order in which
+/// they were added to the context. Variables that were not added are shown using their index.
+#[derive(Debug, Default)]
+pub struct VarNames {
+    vars: Vec<TyVar>,
+}
+
+impl VarNames {
+    /// Create a new and empty context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give a name to every variable inside a type that does not have a name yet.
+    pub fn add(&mut self, ty: &Ty) {
+        for var in ty.free_vars() {
+            if !self.vars.contains(&var) {
+                self.vars.push(var);
+            }
+        }
+    }
+}
+
+impl Show<VarNames> for TyVar {
+    fn show(&self, ctx: &VarNames, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match ctx.vars.iter().position(|var| var == self) {
+            Some(pos) => {
+                let letter = (b'a' + (pos % 26) as u8) as char;
+                write!(f, "'{}", letter)?;
+                // Add a suffix after running out of letters.
+                if pos >= 26 {
+                    write!(f, "{}", pos / 26)?;
+                }
+                Ok(())
+            }
+            None => self.show(&(), f),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::base::BaseTy;
+use crate::{base::BaseTy, AdtId};
 
 use pijama_utils::{new_index, show::Show};
 
@@ -8,8 +8,8 @@ new_index! {
     TyVar
 }
 
-impl<Ctx> Show<Ctx> for TyVar {
-    fn show(&self, _ctx: &Ctx, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Show<()> for TyVar {
+    fn show(&self, _ctx: &(), f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "?T{}", self.0)
     }
 }
@@ -17,14 +17,24 @@ impl<Ctx> Show<Ctx> for TyVar {
 /// A type with holes.
 ///
 /// This is the type representation used for type-checking and type inference. The only difference
-/// between this representation and the concrete representation found in [crate::ty::Ty] is the
-/// [Ty::Var] variant.
+/// between this representation and the concrete representation found in [crate::ty::Ty] are the
+/// [Ty::Var], [Ty::Param], [Ty::Alias] and [Ty::Error] variants.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Ty {
     /// A base type.
     Base(BaseTy),
     /// A type to be infered.
     Var(TyVar),
+    /// A type parameter of a function.
+    ///
+    /// Inside the function, this is a rigid type variable: it is only equal to itself. The
+    /// variable is replaced by other types when the function is instantiated.
+    Param {
+        /// The variable of the parameter.
+        var: TyVar,
+        /// The name of the parameter, used to display it.
+        name: String,
+    },
     /// A function type.
     Func {
         /// The type of each parameter.
@@ -34,24 +44,124 @@ pub enum Ty {
     },
     /// A tuple type.
     Tuple { fields: Vec<Self> },
+    /// A record type.
+    ///
+    /// The fields are stored in the order they were declared.
+    Record {
+        /// The name of the type, used to display it.
+        name: String,
+        /// The name and type of each field.
+        fields: Vec<(String, Self)>,
+    },
+    /// An algebraic data type.
+    ///
+    /// These types are nominal: two of them are equal only if they have the same ID.
+    Adt {
+        /// The ID of the type's definition.
+        id: AdtId,
+        /// The name of the type, used to display it.
+        name: String,
+    },
+    /// A type alias.
+    ///
+    /// An alias is equal to the type it stands for. Its name is kept only to display it.
+    Alias {
+        /// The name of the alias.
+        name: String,
+        /// The type the alias stands for.
+        ty: Box<Self>,
+    },
+    /// The type of an expression that is not well-typed.
+    ///
+    /// This type is equal to any other type so a single error does not cause more errors.
+    Error,
 }
 
 impl Ty {
     /// Check if the current type contains a type variable.
     pub fn contains_ty(&self, target: TyVar) -> bool {
         match self {
-            Ty::Base(_) => false,
+            Ty::Base(_) | Ty::Param { .. } | Ty::Adt { .. } | Ty::Error => false,
             Ty::Var(var) => *var == target,
             Ty::Func {
                 params_ty,
                 return_ty,
             } => params_ty.iter().any(|ty| ty.contains_ty(target)) || return_ty.contains_ty(target),
             Ty::Tuple { fields } => fields.iter().any(|ty| ty.contains_ty(target)),
+            Ty::Record { fields, .. } => fields.iter().any(|(_, ty)| ty.contains_ty(target)),
+            Ty::Alias { ty, .. } => ty.contains_ty(target),
+        }
+    }
+
+    /// Check if the current type is an alias.
+    pub fn is_alias(&self) -> bool {
+        matches!(self, Ty::Alias { .. })
+    }
+
+    /// Get the type the current type stands for if it is an alias, or the type itself
+    /// otherwise.
+    pub fn unaliased(&self) -> &Self {
+        match self {
+            Ty::Alias { ty, .. } => ty.unaliased(),
+            ty => ty,
+        }
+    }
+
+    /// Consume the current type and return the type it stands for if it is an alias, or the type
+    /// itself otherwise.
+    pub fn into_unaliased(self) -> Self {
+        match self {
+            Ty::Alias { ty, .. } => ty.into_unaliased(),
+            ty => ty,
+        }
+    }
+
+    /// Get the type variables of the current type in order of appearance and without
+    /// duplicates.
+    ///
+    /// The variables of type parameters are not included because they cannot be infered.
+    pub fn free_vars(&self) -> Vec<TyVar> {
+        let mut vars = Vec::new();
+        self.collect_vars(&mut vars);
+        vars
+    }
+
+    fn collect_vars(&self, vars: &mut Vec<TyVar>) {
+        match self {
+            Ty::Base(_) | Ty::Param { .. } | Ty::Adt { .. } | Ty::Error => (),
+            Ty::Var(var) => {
+                if !vars.contains(var) {
+                    vars.push(*var);
+                }
+            }
+            Ty::Func {
+                params_ty,
+                return_ty,
+            } => {
+                for ty in params_ty {
+                    ty.collect_vars(vars);
+                }
+                return_ty.collect_vars(vars);
+            }
+            Ty::Tuple { fields } => {
+                for ty in fields {
+                    ty.collect_vars(vars);
+                }
+            }
+            Ty::Record { fields, .. } => {
+                for (_, ty) in fields {
+                    ty.collect_vars(vars);
+                }
+            }
+            Ty::Alias { ty, .. } => ty.collect_vars(vars),
         }
     }
 }
 
-impl<Ctx> Show<Ctx> for Ty {
+impl<Ctx> Show<Ctx> for Ty
+where
+    TyVar: Show<Ctx>,
+{
     fn show(&self, ctx: &Ctx, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Base(base_ty) => base_ty.show(ctx, f),
@@ -70,6 +180,11 @@ impl<Ctx> Show<Ctx> for Ty {
             Self::Tuple { fields } => {
                 write!(f, "({})", Show::<Ctx>::show_sep(fields, ", ").wrap(ctx))
             }
+            Self::Param { name, .. }
+            | Self::Record { name, .. }
+            | Self::Adt { name, .. }
+            | Self::Alias { name, .. } => write!(f, "{}", name),
+            Self::Error => write!(f, "{{error}}"),
         }
     }
 }
@@ -9,3 +9,9 @@ new_index! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     ExprId
 }
+
+new_index! {
+    #[doc = "An unique identifier for algebraic data types."]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    AdtId
+}
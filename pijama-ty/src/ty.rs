@@ -1,12 +1,12 @@
 //! Concrete types.
 //!
 //! This is the type representation used after type inference.
-use crate::base::BaseTy;
+use crate::{base::BaseTy, AdtId};
 
 use pijama_utils::show::Show;
 
 /// A concrete type.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Ty {
     /// A base type.
     Base(BaseTy),
@@ -19,6 +19,22 @@ pub enum Ty {
     },
     /// A tuple type.
     Tuple { fields: Vec<Self> },
+    /// A record type.
+    ///
+    /// The fields are stored in the order they were declared.
+    Record {
+        /// The name of the type, used to display it.
+        name: String,
+        /// The name and type of each field.
+        fields: Vec<(String, Self)>,
+    },
+    /// An algebraic data type.
+    Adt {
+        /// The ID of the type's definition.
+        id: AdtId,
+        /// The name of the type, used to display it.
+        name: String,
+    },
 }
 
 impl<Ctx> Show<Ctx> for Ty {
@@ -39,6 +55,7 @@ impl<Ctx> Show<Ctx> for Ty {
             Self::Tuple { fields } => {
                 write!(f, "({})", Show::<Ctx>::show_sep(fields, ", ").wrap(ctx))
             }
+            Self::Record { name, .. } | Self::Adt { name, .. } => write!(f, "{}", name),
         }
     }
 }
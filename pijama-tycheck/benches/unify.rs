@@ -0,0 +1,80 @@
+//! Benchmarks for the type-checker.
+//!
+//! Run them with `cargo bench -p pijama-tycheck`.
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+use pijama_ty::inference::TyContext;
+
+use std::fmt::Write;
+
+/// Generate a program with `len` functions, where each function calls the previous one.
+fn many_functions(len: usize) -> String {
+    let mut source = String::from("fn f0(x, y) do x + 1 end\n");
+
+    for i in 1..len {
+        writeln!(
+            source,
+            "fn f{i}(x, y) do let a = f{j}(x, y); let b = (a, y); if y do a else x end end",
+            i = i,
+            j = i - 1
+        )
+        .unwrap();
+    }
+
+    writeln!(source, "fn main() do f{}(1, true) end", len - 1).unwrap();
+
+    source
+}
+
+/// Generate a program with a single function with `len` nested bindings.
+fn large_function(len: usize) -> String {
+    let mut source = String::from("fn main() do let x0 = 0;\n");
+
+    for i in 1..len {
+        writeln!(
+            source,
+            "let y{i} = (x{j}, true); let x{i} = if false do x{j} + 1 else x{j} end;",
+            i = i,
+            j = i - 1
+        )
+        .unwrap();
+    }
+
+    writeln!(source, "x{} end", len - 1).unwrap();
+
+    source
+}
+
+fn bench_program(c: &mut Criterion, name: &str, generate: fn(usize) -> String, lens: &[usize]) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+
+    for &len in lens {
+        let source = generate(len);
+
+        group.bench_with_input(BenchmarkId::from_parameter(len), &source, |b, source| {
+            // Only the type-checking is measured.
+            b.iter_batched(
+                || {
+                    let tcx = TyContext::new();
+                    let (ast, errors) = pijama_parser::parse(source);
+                    assert!(errors.is_empty());
+                    let hir = pijama_ast_lowering::lower_ast(&tcx, ast).unwrap();
+                    (tcx, hir)
+                },
+                |(tcx, hir)| pijama_tycheck::check_program(&tcx, &hir).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    bench_program(c, "many_functions", many_functions, &[250, 1000, 4000]);
+    bench_program(c, "large_function", large_function, &[100, 500, 2000]);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
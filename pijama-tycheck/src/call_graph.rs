@@ -0,0 +1,171 @@
+use pijama_hir::{Atom, Expr, ExprKind, FuncId, Name, Program};
+use pijama_utils::index::IndexMap;
+
+/// Compute the strongly connected components of the call graph of a program.
+///
+/// Two functions are in the same component if they are mutually recursive. The components are
+/// returned in reverse topological order, which means that the functions used by a component are
+/// in the same component or in a component that comes before it.
+pub(crate) fn sccs(program: &Program) -> Vec<Vec<FuncId>> {
+    // Find the functions used by each function.
+    let edges = program
+        .functions
+        .iter()
+        .map(|(_, func)| {
+            let mut callees = Vec::new();
+            find_callees(&func.body, &mut callees);
+            callees
+        })
+        .collect();
+
+    let mut tarjan = Tarjan {
+        edges: IndexMap::from_raw(edges),
+        indices: IndexMap::from_raw(vec![None; program.functions.len()]),
+        low_links: IndexMap::from_raw(vec![0; program.functions.len()]),
+        on_stack: IndexMap::from_raw(vec![false; program.functions.len()]),
+        stack: Vec::new(),
+        next_index: 0,
+        sccs: Vec::new(),
+    };
+
+    for (func_id, _) in program.functions.iter() {
+        if tarjan.indices.get(func_id).unwrap().is_none() {
+            tarjan.visit(func_id);
+        }
+    }
+
+    tarjan.sccs
+}
+
+/// Push the ID of every function used inside an expression.
+fn find_callees(expr: &Expr, callees: &mut Vec<FuncId>) {
+    match &expr.kind {
+        ExprKind::Atom(Atom::Name(Name::FuncPtr(func_id)))
+        | ExprKind::Instantiate { func: func_id, .. }
+            if !callees.contains(func_id) =>
+        {
+            callees.push(*func_id)
+        }
+        _ => (),
+    }
+
+    match &expr.kind {
+        ExprKind::Atom(_) | ExprKind::Instantiate { .. } => (),
+        ExprKind::Let { rhs, body, .. } => {
+            find_callees(rhs, callees);
+            find_callees(body, callees);
+        }
+        ExprKind::Call { func, args } => {
+            find_callees(func, callees);
+            for arg in args {
+                find_callees(arg, callees);
+            }
+        }
+        ExprKind::UnaryOp { op, .. } => find_callees(op, callees),
+        ExprKind::BinaryOp {
+            left_op, right_op, ..
+        } => {
+            find_callees(left_op, callees);
+            find_callees(right_op, callees);
+        }
+        ExprKind::Cond {
+            cond,
+            do_branch,
+            else_branch,
+        } => {
+            find_callees(cond, callees);
+            find_callees(do_branch, callees);
+            find_callees(else_branch, callees);
+        }
+        ExprKind::Tuple { fields } | ExprKind::Construct { args: fields, .. } => {
+            for field in fields {
+                find_callees(field, callees);
+            }
+        }
+        ExprKind::Record { fields, .. } => {
+            for (_, field) in fields {
+                find_callees(field, callees);
+            }
+        }
+        ExprKind::Projection { tuple: record, .. } | ExprKind::Field { record, .. } => {
+            find_callees(record, callees)
+        }
+        ExprKind::Update { record, fields } => {
+            find_callees(record, callees);
+            for (_, field) in fields {
+                find_callees(field, callees);
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            find_callees(scrutinee, callees);
+            for arm in arms {
+                find_callees(&arm.body, callees);
+            }
+        }
+        ExprKind::Lambda { body, .. } => find_callees(body, callees),
+    }
+}
+
+/// The state of Tarjan's strongly connected components algorithm.
+struct Tarjan {
+    /// The functions used by each function.
+    edges: IndexMap<FuncId, Vec<FuncId>>,
+    /// The order in which each function was visited, if it was visited.
+    indices: IndexMap<FuncId, Option<usize>>,
+    /// The smallest index reachable from each function.
+    low_links: IndexMap<FuncId, usize>,
+    /// Whether a function is in the stack or not.
+    on_stack: IndexMap<FuncId, bool>,
+    stack: Vec<FuncId>,
+    next_index: usize,
+    /// The components found so far.
+    sccs: Vec<Vec<FuncId>>,
+}
+
+impl Tarjan {
+    fn visit(&mut self, func_id: FuncId) {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        *self.indices.get_mut(func_id).unwrap() = Some(index);
+        *self.low_links.get_mut(func_id).unwrap() = index;
+        *self.on_stack.get_mut(func_id).unwrap() = true;
+        self.stack.push(func_id);
+
+        for callee in self.edges.get(func_id).unwrap().clone() {
+            match *self.indices.get(callee).unwrap() {
+                // The callee has not been visited yet.
+                None => {
+                    self.visit(callee);
+                    let low_link = *self.low_links.get(callee).unwrap();
+                    let current = self.low_links.get_mut(func_id).unwrap();
+                    *current = (*current).min(low_link);
+                }
+                // The callee is in the current component.
+                Some(callee_index) if *self.on_stack.get(callee).unwrap() => {
+                    let current = self.low_links.get_mut(func_id).unwrap();
+                    *current = (*current).min(callee_index);
+                }
+                // The callee is in a component that was already found.
+                Some(_) => (),
+            }
+        }
+
+        // If this function is the root of a component, pop the whole component from the stack.
+        if *self.low_links.get(func_id).unwrap() == index {
+            let mut scc = Vec::new();
+
+            loop {
+                let member = self.stack.pop().expect("The stack cannot be empty.");
+                *self.on_stack.get_mut(member).unwrap() = false;
+                scc.push(member);
+
+                if member == func_id {
+                    break;
+                }
+            }
+
+            self.sccs.push(scc);
+        }
+    }
+}
@@ -1,22 +1,24 @@
 use crate::{
-    constraint::Constraint,
-    error::{TyError, TyResult},
+    call_graph,
+    constraint::{Constraint, Field, FieldConstraint},
+    error::{TyError, TyErrorKind, TyResult},
+    exhaustiveness::Match,
     inference::InferTy,
-    substitution::Substitution,
+    scheme::Scheme,
     table::{Table, TableBuilder},
     unifier::{Unifier, UnifierBuilder},
 };
 
-use pijama_hir::{FuncId, Local, Name, Program};
+use pijama_hir::{AdtDef, FuncId, Local, Name, Pattern, Program, Variant};
 use pijama_ty::{
-    inference::{Ty, TyContext},
-    ExprId,
+    inference::{Ty, TyContext, TyVar},
+    AdtId, ExprId,
 };
-use pijama_utils::{index::IndexMap, show::Show};
+use pijama_utils::{index::IndexMap, show::Show, span::Span};
 
 use log::{info, trace};
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 pub(crate) struct Checker<'tcx> {
     /// The typing context.
@@ -25,26 +27,69 @@ pub(crate) struct Checker<'tcx> {
     locals_ty: IndexMap<Local, Ty>,
     /// The types of all the functions in the program.
     funcs_ty: IndexMap<FuncId, Ty>,
+    /// The type parameters of all the functions in the program.
+    ty_params: IndexMap<FuncId, Vec<Ty>>,
+    /// The type schemes of the functions that have already been generalized.
+    schemes: IndexMap<FuncId, Option<Scheme>>,
+    /// The types that replace the quantified variables of each function at each expression where
+    /// it is used.
+    instances: HashMap<ExprId, Vec<Ty>>,
+    /// The uses of functions that belong to the component being type-checked.
+    component_uses: Vec<(ExprId, FuncId)>,
     /// The set of constraints that the program must satisfy to be well-typed.
     constraints: VecDeque<Constraint>,
+    /// The field constraints that have not been solved yet.
+    field_constraints: Vec<FieldConstraint>,
+    /// The `match` expressions whose patterns have not been checked yet.
+    matches: Vec<Match>,
     table: TableBuilder,
+    /// The expressions whose types were infered since the last time this field was taken.
+    exprs: Vec<ExprId>,
+    /// The spans of the expressions of the program.
+    spans: &'tcx IndexMap<ExprId, Span>,
+    /// The algebraic data types of the program.
+    adts: &'tcx IndexMap<AdtId, AdtDef>,
+    /// The errors found so far.
+    errors: Vec<TyError>,
 }
 
 impl<'tcx> Checker<'tcx> {
     /// Return a new checker.
-    pub(crate) fn new(tcx: &'tcx TyContext) -> Self {
+    pub(crate) fn new(
+        tcx: &'tcx TyContext,
+        spans: &'tcx IndexMap<ExprId, Span>,
+        adts: &'tcx IndexMap<AdtId, AdtDef>,
+    ) -> Self {
         Self {
             tcx,
             locals_ty: IndexMap::new(),
             funcs_ty: IndexMap::new(),
+            ty_params: IndexMap::new(),
+            schemes: IndexMap::new(),
+            instances: HashMap::new(),
+            component_uses: Vec::new(),
             constraints: VecDeque::new(),
+            field_constraints: Vec::new(),
+            matches: Vec::new(),
             table: Table::builder(tcx.count_expr_ids()),
+            exprs: Vec::new(),
+            spans,
+            adts,
+            errors: Vec::new(),
         }
     }
 
     /// Type-check a program, consuming the checker in the process. If the type-checking was
-    /// successful, return an [Unifier] to instantiate all the type variables.
-    pub(crate) fn check_program(mut self, program: &Program) -> TyResult<(Unifier, Table)> {
+    /// successful, return an [Unifier] to instantiate all the type variables. Otherwise, return
+    /// every error found sorted by location.
+    ///
+    /// Functions are type-checked following the strongly connected components of the call graph,
+    /// so every function is generalized before being used by the functions that do not belong to
+    /// its component.
+    pub(crate) fn check_program(
+        mut self,
+        program: &Program,
+    ) -> Result<(Unifier, TableBuilder), Vec<TyError>> {
         // Reconstruct the type of each function in the program.
         let funcs_ty = program
             .functions
@@ -62,98 +107,462 @@ impl<'tcx> Checker<'tcx> {
         // FIXME: maybe it is better to do this during initialization?.
         // Put the types of the function into the checker.
         self.funcs_ty = IndexMap::from_raw(funcs_ty);
+        self.ty_params = IndexMap::from_raw(
+            program
+                .functions
+                .iter()
+                .map(|(_, func)| func.ty_params.clone())
+                .collect(),
+        );
+        self.schemes = IndexMap::from_raw(vec![None; program.functions.len()]);
 
-        // Type-check every function.
-        for (_, func) in &program.functions {
-            // Put the types of the locals in the checker.
-            self.locals_ty = func.locals.clone();
-            // Infer the type of the body of the function.
-            let body_ty = func.body.infer_ty(&mut self)?;
-            // The type of the body must be equal to the return type of the function.
-            self.add_constraint(func.return_ty.clone(), body_ty);
-        }
+        // The expressions of each function.
+        let mut funcs_exprs = IndexMap::from_raw(vec![Vec::new(); program.functions.len()]);
 
-        // Unify all the constraints.
         let mut builder = Unifier::builder();
-        self.unify(&mut builder)?;
 
-        // Build an unifier.
-        let unifier = builder.build()?;
+        for scc in call_graph::sccs(program) {
+            // Type-check every function in the component.
+            for &func_id in &scc {
+                let func = program.functions.get(func_id).unwrap();
+                // Put the types of the locals in the checker.
+                self.locals_ty = func.locals.clone();
+                // Infer the type of the body of the function.
+                match func.body.infer_ty(&mut self) {
+                    Ok(body_ty) => {
+                        // The type of the body must be equal to the return type of the function.
+                        let span = self.expr_span(func.body.id);
+                        self.add_constraint(func.return_ty.clone(), body_ty, span);
+                    }
+                    Err(error) => self.errors.push(error),
+                }
+                // Keep the expressions of the function to check them later.
+                *funcs_exprs.get_mut(func_id).unwrap() = std::mem::take(&mut self.exprs);
+            }
+
+            // Unify all the constraints of the component.
+            self.unify(&mut builder);
+            self.solve_field_constraints(&mut builder);
+
+            // The types of the field constraints that are still pending might be found when
+            // checking the functions that use this component, so they cannot be generalized.
+            let pending = self.pending_vars(&mut builder);
+
+            // Generalize the type of every function in the component. The main function cannot be
+            // polymorphic because there is nobody to instantiate it.
+            for func_id in scc {
+                let mut ty = self.funcs_ty.get(func_id).unwrap().clone();
+                builder.apply_substitutions(&mut ty);
+
+                let scheme = if func_id == FuncId::main() {
+                    Scheme::mono(ty)
+                } else {
+                    Scheme::generalize(ty, self.ty_params.get(func_id).unwrap(), &pending)
+                };
+
+                info!(
+                    "Generalized function {:?}: {}.",
+                    func_id,
+                    scheme.ty.wrap(&())
+                );
+
+                *self.schemes.get_mut(func_id).unwrap() = Some(scheme);
+            }
+
+            // The functions of the component use each other with their non-generalized types, so
+            // their quantified variables are replaced by themselves.
+            for (expr_id, func_id) in std::mem::take(&mut self.component_uses) {
+                let ty_params = self.ty_params.get(func_id).unwrap();
+                let scheme = self.schemes.get(func_id).unwrap().as_ref().unwrap();
+                let args = scheme
+                    .vars
+                    .iter()
+                    .map(|&var| {
+                        ty_params
+                            .iter()
+                            .find(|param| match param {
+                                Ty::Param { var: param_var, .. } => *param_var == var,
+                                _ => false,
+                            })
+                            .cloned()
+                            .unwrap_or(Ty::Var(var))
+                    })
+                    .collect();
+
+                self.instances.insert(expr_id, args);
+            }
+        }
+
+        // No other function can reveal the types of the remaining field constraints.
+        self.report_field_constraints(&mut builder);
+
+        let schemes = IndexMap::from_raw(
+            std::mem::replace(&mut self.schemes, IndexMap::new())
+                .into_raw()
+                .into_iter()
+                .map(|scheme| scheme.expect("Every function should have been generalized."))
+                .collect(),
+        );
+
+        let mut errors = std::mem::take(&mut self.errors);
+
+        // The main function is called without arguments when the program starts and its result
+        // is returned to the caller, so it can only return integers and booleans.
+        let main = program.functions.get(FuncId::main()).unwrap();
+        if let Ty::Func {
+            params_ty,
+            return_ty,
+        } = &schemes.get(FuncId::main()).unwrap().ty
+        {
+            // The return type might contain errors if the program is not well-typed.
+            let well_typed = errors.is_empty();
+
+            if !params_ty.is_empty() {
+                let (_, span) = main.local_spans.iter().next().unwrap();
+                errors.push(TyErrorKind::MainParams.into_err(*span));
+            }
+
+            // Variables are reported below if their type cannot be infered.
+            if well_typed && !matches!(**return_ty, Ty::Base(_) | Ty::Var(_)) {
+                let ty = (**return_ty).clone();
+                let span = self.expr_span(main.body.id);
+                errors.push(TyErrorKind::MainReturnTy { ty }.into_err(span));
+            }
+        }
+
+        // Every type variable in a function must be either a quantified variable of the function
+        // or have a concrete type. If not, report it using the location of the first expression
+        // or local whose type contains it.
+        for (func_id, func) in program.functions.iter() {
+            let scheme = schemes.get(func_id).unwrap();
+
+            let exprs_ty = funcs_exprs
+                .get(func_id)
+                .unwrap()
+                .iter()
+                .map(|&expr_id| (self.table.get_ty(expr_id).unwrap(), self.expr_span(expr_id)));
+            let locals_ty = func
+                .locals
+                .iter()
+                .zip(func.local_spans.iter())
+                .map(|((_, ty), (_, span))| (ty, *span));
+
+            for (ty, span) in exprs_ty.chain(locals_ty) {
+                let mut ty = ty.clone();
+                builder.apply_substitutions(&mut ty);
+
+                if let Some(var) = ty
+                    .free_vars()
+                    .into_iter()
+                    .find(|var| !scheme.vars.contains(var))
+                {
+                    // Poison the variable so it is reported only once.
+                    builder.poison(&Ty::Var(var));
+                    errors.push(TyErrorKind::FoundVar { var, ty }.into_err(span));
+                }
+            }
+        }
+
+        // The patterns are only checked if the program is well-typed, otherwise the types of the
+        // scrutinees might be wrong.
+        if errors.is_empty() {
+            for mut match_ in std::mem::take(&mut self.matches) {
+                builder.apply_substitutions(&mut match_.scrutinee_ty);
+                match_.check(self.adts, &mut errors);
+            }
+        }
+
+        if !errors.is_empty() {
+            errors.sort_by_key(|error| error.span.start);
+            return Err(errors);
+        }
+
+        let unifier = builder.build(schemes, self.instances);
 
-        let table = self.table.build(&unifier).unwrap();
+        Ok((unifier, self.table))
+    }
 
-        Ok((unifier, table))
+    /// Get the type of a local.
+    pub(crate) fn get_local_ty(&self, local: Local) -> Option<&Ty> {
+        self.locals_ty.get(local)
     }
 
-    /// Get the type of a name.
-    pub(crate) fn get_name_ty(&self, name: &Name) -> Option<&Ty> {
+    /// Get the type of a name used by an expression.
+    ///
+    /// If the name is a function that has already been generalized, its type scheme is
+    /// instantiated with new type variables. Otherwise, the function belongs to the component
+    /// being type-checked and it is used with its non-generalized type.
+    pub(crate) fn get_name_ty(&mut self, name: &Name, expr_id: ExprId) -> Option<Ty> {
         match name {
-            Name::Local(local) => self.locals_ty.get(*local),
-            Name::FuncPtr(func_id) => self.funcs_ty.get(*func_id),
+            Name::Local(local) => self.get_local_ty(*local).cloned(),
+            Name::FuncPtr(func_id) => {
+                let ty = match self.schemes.get(*func_id)? {
+                    Some(scheme) => {
+                        let (ty, args) = scheme.instantiate(self.tcx, &[]);
+                        // Record the types of the quantified variables for this use.
+                        self.instances.insert(expr_id, args);
+                        ty
+                    }
+                    None => {
+                        self.component_uses.push((expr_id, *func_id));
+                        self.funcs_ty.get(*func_id)?.clone()
+                    }
+                };
+
+                Some(ty)
+            }
         }
     }
 
+    /// Get the type of a function used by an expression with explicit type arguments.
+    ///
+    /// The type arguments replace the type parameters of the function in order. If the function
+    /// belongs to the component being type-checked, its type parameters must be equal to the type
+    /// arguments instead.
+    pub(crate) fn get_func_ty_with(
+        &mut self,
+        func_id: FuncId,
+        ty_args: &[Ty],
+        expr_id: ExprId,
+    ) -> TyResult<Ty> {
+        let span = self.expr_span(expr_id);
+        let ty_params = self.ty_params.get(func_id).unwrap().clone();
+
+        if ty_params.len() != ty_args.len() {
+            return Err(TyErrorKind::TyArgsMismatch {
+                expected: ty_params.len(),
+                found: ty_args.len(),
+            }
+            .into_err(span));
+        }
+
+        let ty = match self.schemes.get(func_id).unwrap() {
+            Some(scheme) => {
+                let (ty, args) = scheme.instantiate(self.tcx, ty_args);
+                // Record the types of the quantified variables for this use.
+                self.instances.insert(expr_id, args);
+                ty
+            }
+            None => {
+                for (ty_param, ty_arg) in ty_params.into_iter().zip(ty_args) {
+                    self.add_constraint(ty_param, ty_arg.clone(), span);
+                }
+
+                self.component_uses.push((expr_id, func_id));
+                self.funcs_ty.get(func_id).unwrap().clone()
+            }
+        };
+
+        Ok(ty)
+    }
+
+    /// Get the type of an algebraic data type.
+    pub(crate) fn get_adt_ty(&self, adt: AdtId) -> Ty {
+        let name = self
+            .adts
+            .get(adt)
+            .expect("Every type should have a definition.")
+            .name
+            .clone();
+
+        Ty::Adt { id: adt, name }
+    }
+
+    /// Get a variant of an algebraic data type.
+    pub(crate) fn get_variant(&self, adt: AdtId, variant: usize) -> &'tcx Variant {
+        &self
+            .adts
+            .get(adt)
+            .expect("Every type should have a definition.")
+            .variants[variant]
+    }
+
+    /// Get the span of an expression.
+    pub(crate) fn expr_span(&self, expr_id: ExprId) -> Span {
+        *self
+            .spans
+            .get(expr_id)
+            .expect("Every expression should have a span.")
+    }
+
     /// Add a constraint that the program must satisfy to be well-typed.
-    pub(crate) fn add_constraint(&mut self, lhs: Ty, rhs: Ty) {
+    ///
+    /// The span is the location of the code that introduced the constraint and it is used to
+    /// report an error if the constraint cannot be satisfied.
+    pub(crate) fn add_constraint(&mut self, lhs: Ty, rhs: Ty, span: Span) {
         info!("Adding constraint: {} = {}.", lhs.wrap(&()), rhs.wrap(&()));
 
-        self.constraints.push_front(Constraint::new(lhs, rhs));
+        self.constraints.push_front(Constraint::new(lhs, rhs, span));
+    }
+
+    /// Add a constraint stating that a type must be a tuple or a record with a field of a certain
+    /// type.
+    pub(crate) fn add_field_constraint(&mut self, ty: Ty, field: Field, field_ty: Ty, span: Span) {
+        info!(
+            "Adding field constraint: {}.{} = {}.",
+            ty.wrap(&()),
+            field,
+            field_ty.wrap(&())
+        );
+
+        self.field_constraints.push(FieldConstraint {
+            ty,
+            field,
+            field_ty,
+            span,
+        });
+    }
+
+    /// Add a `match` expression whose patterns must be checked once the type of its scrutinee is
+    /// known.
+    pub(crate) fn add_match(&mut self, scrutinee_ty: Ty, patterns: Vec<Pattern>, span: Span) {
+        self.matches.push(Match {
+            scrutinee_ty,
+            patterns,
+            span,
+        });
+    }
+
+    /// Solve the field constraints.
+    ///
+    /// A field constraint can be solved once the type with the field is known. Solving it adds
+    /// new constraints that might reveal other types, so this is repeated until no more field
+    /// constraints can be solved. The constraints whose types are still unknown are kept for
+    /// later.
+    fn solve_field_constraints(&mut self, builder: &mut UnifierBuilder) {
+        loop {
+            let mut solved = false;
+
+            for constraint in std::mem::take(&mut self.field_constraints) {
+                let FieldConstraint {
+                    ty,
+                    field,
+                    field_ty,
+                    span,
+                } = constraint;
+
+                let ty = builder.shallow_resolve(ty);
+
+                match (ty.unaliased(), &field) {
+                    // The type with the field is not known yet.
+                    (Ty::Var(_), _) => {
+                        self.field_constraints.push(FieldConstraint {
+                            ty,
+                            field,
+                            field_ty,
+                            span,
+                        });
+                        continue;
+                    }
+                    (Ty::Tuple { fields }, Field::Index(index)) if *index < fields.len() => {
+                        self.add_constraint(fields[*index].clone(), field_ty, span);
+                    }
+                    (Ty::Record { fields, .. }, Field::Name(name))
+                        if fields.iter().any(|(field, _)| field == name) =>
+                    {
+                        let (_, ty) = fields.iter().find(|(field, _)| field == name).unwrap();
+                        self.add_constraint(ty.clone(), field_ty, span);
+                    }
+                    (Ty::Error, _) => builder.poison(&field_ty),
+                    _ => {
+                        // Keep the aliases of the type so the error shows their names.
+                        let mut ty = ty;
+                        builder.apply_substitutions(&mut ty);
+                        builder.poison(&field_ty);
+
+                        self.errors
+                            .push(TyErrorKind::NoField { ty, field }.into_err(span));
+                    }
+                }
+
+                solved = true;
+            }
+
+            if !solved {
+                break;
+            }
+
+            self.unify(builder);
+        }
+    }
+
+    /// Get the type variables of the field constraints that have not been solved yet.
+    fn pending_vars(&self, builder: &mut UnifierBuilder) -> Vec<TyVar> {
+        let mut vars = Vec::new();
+
+        for constraint in &self.field_constraints {
+            for ty in [&constraint.ty, &constraint.field_ty].iter() {
+                let mut ty = (*ty).clone();
+                builder.apply_substitutions(&mut ty);
+                vars.extend(ty.free_vars());
+            }
+        }
+
+        vars
     }
 
-    /// Apply a substitution to all the remaining constraints.
-    fn update_constraints(&mut self, subst: &Substitution) {
-        info!("Applying substitution {}.", subst.wrap(&()));
+    /// Report the field constraints whose types are still unknown as errors.
+    fn report_field_constraints(&mut self, builder: &mut UnifierBuilder) {
+        for FieldConstraint {
+            ty, field_ty, span, ..
+        } in std::mem::take(&mut self.field_constraints)
+        {
+            // The constraint might have been solved by poisoning its type.
+            if let Ty::Var(var) = builder.shallow_resolve(ty) {
+                builder.poison(&Ty::Var(var));
+                builder.poison(&field_ty);
 
-        for Constraint { lhs, rhs } in &mut self.constraints {
-            subst.apply_to(lhs);
-            subst.apply_to(rhs);
+                self.errors.push(
+                    TyErrorKind::FoundVar {
+                        var,
+                        ty: Ty::Var(var),
+                    }
+                    .into_err(span),
+                );
+            }
         }
     }
 
     /// Unify the set of constraints.
     ///
-    /// If this function runs successfully, the `substitutions` field can be used to substitute any
-    /// type.
-    fn unify(&mut self, builder: &mut UnifierBuilder) -> TyResult {
-        // FIXME: check if it is better to pop from the other end.
+    /// Constraints are solved one by one using a worklist. New constraints are pushed to the
+    /// worklist when both sides of a constraint are functions, tuples or records. When a
+    /// constraint cannot be satisfied, the error is stored and the type variables on both sides
+    /// are poisoned so they do not cause more errors. After this function runs, the builder can
+    /// be used to substitute any type.
+    fn unify(&mut self, builder: &mut UnifierBuilder) {
         // Keep unifying while there are constraints to unify.
-        if let Some(Constraint { lhs, rhs }) = self.constraints.pop_back() {
+        while let Some(Constraint { lhs, rhs, span }) = self.constraints.pop_back() {
             trace!("Solving constraint: {} = {}", lhs.wrap(&()), rhs.wrap(&()));
 
+            // Replace both sides by their types if they are variables bound to a type.
+            let lhs = builder.shallow_resolve(lhs);
+            let rhs = builder.shallow_resolve(rhs);
+
             // Skip the constraint if both sides of the constraint are equal.
             if lhs == rhs {
                 info!("Both sides are equal.");
-                return self.unify(builder);
+                continue;
             }
 
             match (lhs, rhs) {
-                // If the left-hand side type is a free variable in the right-hand side, we can
-                // replace the left-hand side by the right-hand side.
-                (Ty::Var(var), rhs) if !rhs.contains_ty(var) => {
-                    info!("LHS is a free variable in RHS.");
-
-                    let subs = Substitution::new(var, rhs);
-                    // Replace lhs by rhs in all the constraints.
-                    self.update_constraints(&subs);
-                    // Keep unifying.
-                    self.unify(builder)?;
-                    // Add this substitution to the builder.
-                    builder.add_substitution(subs);
+                // If one side is the error type, the variables on the other side are poisoned.
+                (Ty::Error, ty) | (ty, Ty::Error) => {
+                    info!("One side is an error.");
+                    builder.poison(&ty);
+                }
+                // If both sides are unbound variables, they must be in the same set.
+                (Ty::Var(var1), Ty::Var(var2)) => {
+                    info!("Both sides are variables.");
+                    builder.union(var1, var2);
                 }
-                // If the right-hand side type is a free variable in the left-hand side, we can
-                // replace the right-hand side by the left-hand side.
-                (lhs, Ty::Var(var)) if !lhs.contains_ty(var) => {
-                    info!("RHS is a free variable in LHS.");
-
-                    let subs = Substitution::new(var, lhs);
-                    // Replace rhs by lhs in all the constraints.
-                    self.update_constraints(&subs);
-                    // Keep unifying.
-                    self.unify(builder)?;
-                    // Add this substitution to the builder.
-                    builder.add_substitution(subs);
+                // If one side is an unbound variable that does not appear in the other side, we
+                // can bind the variable to the other side.
+                (Ty::Var(var), ty) | (ty, Ty::Var(var)) if !builder.occurs(var, &ty) => {
+                    info!("One side is a free variable in the other side.");
+                    builder.bind(var, ty);
                 }
-                // If both sides are functions. Unify each type inside them recursively.
+                // If both sides are functions. Unify each type inside them.
                 (
                     Ty::Func {
                         params_ty: params_ty1,
@@ -168,52 +577,132 @@ impl<'tcx> Checker<'tcx> {
 
                     // Error if the arities of the functions do not match.
                     if params_ty1.len() != params_ty2.len() {
-                        return Err(TyError::ArityMismatch {
-                            expected: params_ty1.len(),
-                            found: params_ty2.len(),
-                        });
+                        self.errors.push(
+                            TyErrorKind::ArityMismatch {
+                                expected: params_ty1.len(),
+                                found: params_ty2.len(),
+                            }
+                            .into_err(span),
+                        );
+
+                        for ty in params_ty1.iter().chain(params_ty2.iter()) {
+                            builder.poison(ty);
+                        }
+                        builder.poison(&return_ty1);
+                        builder.poison(&return_ty2);
+
+                        continue;
                     }
 
                     // The parameters must be equal one-to-one.
-                    for (lhs, rhs) in params_ty1.into_iter().zip(params_ty2.into_iter()) {
-                        self.add_constraint(lhs, rhs);
+                    for (lhs, rhs) in params_ty1.into_iter().zip(params_ty2) {
+                        self.add_constraint(lhs, rhs, span);
                     }
 
                     // The return types must be equal.
-                    self.add_constraint(*return_ty1, *return_ty2);
-
-                    // Keep unifying.
-                    self.unify(builder)?;
+                    self.add_constraint(*return_ty1, *return_ty2, span);
                 }
-                // If both sides are tuples. Unify each type inside them recursively.
+                // If both sides are tuples. Unify each type inside them.
                 (Ty::Tuple { fields: fields_ty1 }, Ty::Tuple { fields: fields_ty2 }) => {
                     info!("Both sides are tuples.");
 
                     // Error if the lengths of the tuples do not match.
                     if fields_ty1.len() != fields_ty2.len() {
-                        // FIXME: Technically this is not an arity mismatch
-                        return Err(TyError::ArityMismatch {
-                            expected: fields_ty1.len(),
-                            found: fields_ty2.len(),
-                        });
+                        self.errors.push(
+                            TyErrorKind::TupleLenMismatch {
+                                expected: fields_ty1.len(),
+                                found: fields_ty2.len(),
+                            }
+                            .into_err(span),
+                        );
+
+                        for ty in fields_ty1.iter().chain(fields_ty2.iter()) {
+                            builder.poison(ty);
+                        }
+
+                        continue;
                     }
 
                     // The types of the fields must be equal one-to-one.
-                    for (lhs, rhs) in fields_ty1.into_iter().zip(fields_ty2.into_iter()) {
-                        self.add_constraint(lhs, rhs);
+                    for (lhs, rhs) in fields_ty1.into_iter().zip(fields_ty2) {
+                        self.add_constraint(lhs, rhs, span);
                     }
+                }
+                // If both sides are records of the same type. Unify each type inside them.
+                (
+                    Ty::Record {
+                        name: name1,
+                        fields: fields_ty1,
+                    },
+                    Ty::Record {
+                        name: name2,
+                        fields: fields_ty2,
+                    },
+                ) if name1 == name2 => {
+                    info!("Both sides are records.");
+
+                    // The types of the fields must be equal one-to-one.
+                    for ((_, lhs), (_, rhs)) in fields_ty1.into_iter().zip(fields_ty2) {
+                        self.add_constraint(lhs, rhs, span);
+                    }
+                }
+                // If one side is an alias of a type that might be equal to the other side, unify
+                // the types they stand for. Otherwise, the aliases are kept so the error shows
+                // their names.
+                (lhs, rhs)
+                    if (lhs.is_alias() || rhs.is_alias())
+                        && same_head(lhs.unaliased(), rhs.unaliased()) =>
+                {
+                    info!("One side is an alias.");
+                    self.add_constraint(lhs.into_unaliased(), rhs.into_unaliased(), span);
+                }
+                // If one side is a type parameter, the other side is a different type.
+                (Ty::Param { name, .. }, mut ty) | (mut ty, Ty::Param { name, .. }) => {
+                    builder.apply_substitutions(&mut ty);
+                    builder.poison(&ty);
 
-                    // Keep unifying.
-                    self.unify(builder)?;
+                    self.errors
+                        .push(TyErrorKind::RigidParam { name, ty }.into_err(span));
+                }
+                // If one side is a variable that appears in the other side, the constraint would
+                // require an infinite type.
+                (Ty::Var(var), mut ty) | (mut ty, Ty::Var(var)) => {
+                    builder.apply_substitutions(&mut ty);
+                    builder.poison(&ty);
+
+                    self.errors
+                        .push(TyErrorKind::InfiniteType { var, ty }.into_err(span));
                 }
                 // Otherwise, the constraint cannot be satisified.
-                (expected, found) => return Err(TyError::TypeMismatch { expected, found }),
+                (mut expected, mut found) => {
+                    builder.apply_substitutions(&mut expected);
+                    builder.apply_substitutions(&mut found);
+                    builder.poison(&expected);
+                    builder.poison(&found);
+
+                    self.errors
+                        .push(TyErrorKind::TypeMismatch { expected, found }.into_err(span));
+                }
             }
         }
-        Ok(())
     }
 
     pub(crate) fn store_ty(&mut self, expr_id: ExprId, ty: Ty) {
         self.table.store_ty(expr_id, ty);
+        self.exprs.push(expr_id);
+    }
+}
+
+/// Check if two types that are not aliases have the same outermost constructor, so they might
+/// be equal.
+fn same_head(lhs: &Ty, rhs: &Ty) -> bool {
+    match (lhs, rhs) {
+        (Ty::Var(_), _) | (_, Ty::Var(_)) | (Ty::Error, _) | (_, Ty::Error) => true,
+        (Ty::Base(base1), Ty::Base(base2)) => base1 == base2,
+        (Ty::Param { var: var1, .. }, Ty::Param { var: var2, .. }) => var1 == var2,
+        (Ty::Func { .. }, Ty::Func { .. }) | (Ty::Tuple { .. }, Ty::Tuple { .. }) => true,
+        (Ty::Record { name: name1, .. }, Ty::Record { name: name2, .. }) => name1 == name2,
+        (Ty::Adt { id: id1, .. }, Ty::Adt { id: id2, .. }) => id1 == id2,
+        _ => false,
     }
 }
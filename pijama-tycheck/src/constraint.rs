@@ -1,15 +1,53 @@
 use pijama_ty::inference::Ty;
+use pijama_utils::span::Span;
 
+use std::fmt;
+
+/// A constraint stating that two types must be equal.
 pub struct Constraint {
     /// The left-hand side of the constraint. Usually this is the expected type of an expression.
     pub(crate) lhs: Ty,
     /// The right-hand side of the constraint. Usually this is the infered type for an expression.
     pub(crate) rhs: Ty,
+    /// The location of the code that introduced this constraint.
+    pub(crate) span: Span,
 }
 
 impl Constraint {
     /// Create a new constraint.
-    pub(crate) fn new(lhs: Ty, rhs: Ty) -> Self {
-        Self { lhs, rhs }
+    pub(crate) fn new(lhs: Ty, rhs: Ty, span: Span) -> Self {
+        Self { lhs, rhs, span }
+    }
+}
+
+/// A constraint stating that a type must be a tuple or a record with a field of a certain type.
+///
+/// These constraints can only be solved once the type with the field is known.
+pub struct FieldConstraint {
+    /// The type with the field.
+    pub(crate) ty: Ty,
+    /// The field.
+    pub(crate) field: Field,
+    /// The type of the field.
+    pub(crate) field_ty: Ty,
+    /// The location of the code that introduced this constraint.
+    pub(crate) span: Span,
+}
+
+/// A field of a tuple or a record.
+#[derive(Debug)]
+pub enum Field {
+    /// The field of a tuple with this index.
+    Index(usize),
+    /// The field of a record with this name.
+    Name(String),
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{}", index),
+            Self::Name(name) => write!(f, "{}", name),
+        }
     }
 }
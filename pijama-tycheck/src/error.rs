@@ -1,16 +1,163 @@
-use pijama_ty::inference::{Ty, TyVar};
+use crate::constraint::Field;
+
+use pijama_ty::inference::{Ty, TyVar, VarNames};
+use pijama_utils::{show::Show, span::Span, spanned_type};
+
+use std::fmt;
 
 pub type TyResult<T = ()> = Result<T, TyError>;
 
+spanned_type!(pub TyError, TyErrorKind);
+
 /// A type-checking error.
 ///
 /// Each variant here represents the reason why type-checking failed.
 #[derive(Debug)]
-pub enum TyError {
+pub enum TyErrorKind {
     /// The expected arity for a function type does not match the one found.
     ArityMismatch { expected: usize, found: usize },
+    /// The expected length for a tuple type does not match the one found.
+    TupleLenMismatch { expected: usize, found: usize },
     /// The expected type does not match the one found.
     TypeMismatch { expected: Ty, found: Ty },
+    /// A type parameter must be equal to a different type.
+    ///
+    /// Type parameters are rigid, so they are only equal to themselves.
+    RigidParam {
+        /// The name of the type parameter.
+        name: String,
+        /// The other type.
+        ty: Ty,
+    },
+    /// The number of type arguments does not match the number of type parameters.
+    TyArgsMismatch { expected: usize, found: usize },
     /// The infered type still has inference variables in it.
-    FoundVar(TyVar),
+    ///
+    /// This happens when the type of an expression cannot be determined.
+    FoundVar {
+        /// The variable that could not be infered.
+        var: TyVar,
+        /// The infered type containing the variable.
+        ty: Ty,
+    },
+    /// A type does not have this field.
+    NoField { ty: Ty, field: Field },
+    /// The patterns of a `match` expression do not cover every possible value.
+    NonExhaustive {
+        /// A pattern for the values that are not covered.
+        witness: String,
+    },
+    /// The pattern of a `match` arm cannot match any value not matched by the previous arms.
+    UnreachablePattern,
+    /// A type variable must be equal to a type containing itself.
+    InfiniteType {
+        /// The type variable.
+        var: TyVar,
+        /// The type containing the variable.
+        ty: Ty,
+    },
+    /// The `main` function has parameters.
+    MainParams,
+    /// The `main` function returns a type that is not `Int` or `Bool`.
+    MainReturnTy { ty: Ty },
+}
+
+impl TyErrorKind {
+    /// Consume the current kind to return an error.
+    pub(crate) fn into_err(self, span: Span) -> TyError {
+        TyError { kind: self, span }
+    }
+}
+
+impl fmt::Display for TyErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The type variables are named consistently across all the types of the message.
+        let mut names = VarNames::new();
+
+        match self {
+            Self::ArityMismatch { expected, found } => write!(
+                f,
+                "arity mismatch: expected {} {}, found {}",
+                expected,
+                plural(*expected, "parameter"),
+                found
+            ),
+            Self::TupleLenMismatch { expected, found } => write!(
+                f,
+                "tuple length mismatch: expected {} {}, found {}",
+                expected,
+                plural(*expected, "element"),
+                found
+            ),
+            Self::TypeMismatch { expected, found } => {
+                names.add(expected);
+                names.add(found);
+                write!(
+                    f,
+                    "type mismatch: expected `{}`, found `{}`",
+                    expected.wrap(&names),
+                    found.wrap(&names)
+                )
+            }
+            Self::RigidParam { name, ty } => {
+                names.add(ty);
+                write!(
+                    f,
+                    "type mismatch: the type parameter `{}` cannot be `{}`",
+                    name,
+                    ty.wrap(&names)
+                )
+            }
+            Self::TyArgsMismatch { expected, found } => write!(
+                f,
+                "wrong number of type arguments: expected {}, found {}",
+                expected, found
+            ),
+            Self::FoundVar { var, ty } => {
+                write!(f, "type annotations needed: cannot infer the type ")?;
+                if *ty == Ty::Var(*var) {
+                    write!(f, "of this expression")
+                } else {
+                    names.add(ty);
+                    write!(f, "`{}` in `{}`", var.wrap(&names), ty.wrap(&names))
+                }
+            }
+            Self::NoField { ty, field } => {
+                names.add(ty);
+                write!(f, "no field `{}` on type `{}`", field, ty.wrap(&names))
+            }
+            Self::NonExhaustive { witness } => {
+                write!(f, "non-exhaustive patterns: `{}` not covered", witness)
+            }
+            Self::UnreachablePattern => write!(f, "unreachable pattern"),
+            Self::InfiniteType { var, ty } => {
+                names.add(&Ty::Var(*var));
+                names.add(ty);
+                write!(
+                    f,
+                    "cannot construct the infinite type `{} = {}`",
+                    var.wrap(&names),
+                    ty.wrap(&names)
+                )
+            }
+            Self::MainParams => write!(f, "the `main` function cannot have parameters"),
+            Self::MainReturnTy { ty } => {
+                names.add(ty);
+                write!(
+                    f,
+                    "the `main` function must return `Int` or `Bool`, found `{}`",
+                    ty.wrap(&names)
+                )
+            }
+        }
+    }
+}
+
+/// Add an `s` to a word unless the count is one.
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_owned()
+    } else {
+        format!("{}s", word)
+    }
 }
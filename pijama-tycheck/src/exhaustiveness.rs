@@ -0,0 +1,318 @@
+//! Exhaustiveness and reachability checking for `match` expressions.
+//!
+//! Both checks are based on the usefulness algorithm described in "Warnings for pattern
+//! matching" by Luc Maranget: a pattern is useful with respect to a list of patterns if there is
+//! a value that matches it but does not match any pattern in the list. Then, an arm is unreachable
+//! if its pattern is not useful with respect to the patterns of the previous arms, and a `match`
+//! is exhaustive if a wildcard is not useful with respect to all its patterns.
+
+use crate::error::{TyError, TyErrorKind};
+
+use pijama_hir::{AdtDef, Pattern, PatternKind};
+use pijama_ty::{base::BaseTy, inference::Ty, AdtId};
+use pijama_utils::{index::IndexMap, show::Show, span::Span};
+
+/// The algebraic data types of a program.
+type Adts = IndexMap<AdtId, AdtDef>;
+
+/// A `match` expression whose patterns must be checked once the type of its scrutinee is known.
+pub(crate) struct Match {
+    /// The type of the scrutinee.
+    pub(crate) scrutinee_ty: Ty,
+    /// The pattern of each arm.
+    pub(crate) patterns: Vec<Pattern>,
+    /// The location of the whole expression.
+    pub(crate) span: Span,
+}
+
+impl Match {
+    /// Check that every arm is reachable and that the patterns cover every possible value of the
+    /// scrutinee.
+    pub(crate) fn check(&self, adts: &Adts, errors: &mut Vec<TyError>) {
+        let tys = [self.scrutinee_ty.clone()];
+        let mut matrix: Vec<Vec<Pat>> = Vec::new();
+
+        for pattern in &self.patterns {
+            let row = vec![Pat::from(pattern)];
+
+            if !is_useful(&matrix, &row, &tys, adts) {
+                errors.push(TyErrorKind::UnreachablePattern.into_err(pattern.span));
+            }
+
+            matrix.push(row);
+        }
+
+        if let Some(witness) = find_witness(&matrix, &tys, adts) {
+            errors.push(
+                TyErrorKind::NonExhaustive {
+                    witness: witness[0].wrap(adts).to_string(),
+                }
+                .into_err(self.span),
+            );
+        }
+    }
+}
+
+/// A constructor of values that can be tested by a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ctor {
+    Bool(bool),
+    Int(i64),
+    /// A tuple with this number of fields.
+    Tuple(usize),
+    /// A variant of an algebraic data type.
+    Variant {
+        adt: AdtId,
+        /// The position of the variant in the type's definition.
+        index: usize,
+        /// The number of fields of the variant.
+        arity: usize,
+    },
+}
+
+impl Ctor {
+    /// The number of fields of the constructor.
+    fn arity(self) -> usize {
+        match self {
+            Self::Bool(_) | Self::Int(_) => 0,
+            Self::Tuple(arity) | Self::Variant { arity, .. } => arity,
+        }
+    }
+
+    /// The type of each field of the constructor for a value of the given type.
+    fn fields_ty(self, ty: &Ty, adts: &Adts) -> Vec<Ty> {
+        match (self, ty.unaliased()) {
+            (Self::Tuple(arity), Ty::Tuple { fields }) if fields.len() == arity => fields.clone(),
+            (Self::Variant { adt, index, .. }, Ty::Adt { id, .. }) if adt == *id => {
+                adts.get(adt).unwrap().variants[index].fields.clone()
+            }
+            _ => vec![Ty::Error; self.arity()],
+        }
+    }
+}
+
+/// Return every constructor of a type.
+///
+/// Integers have too many constructors to be listed, so `None` is returned for them. The same
+/// happens with any type that cannot be destructured by a pattern.
+fn all_ctors(ty: &Ty, adts: &Adts) -> Option<Vec<Ctor>> {
+    let ctors = match ty.unaliased() {
+        Ty::Base(BaseTy::Bool) => vec![Ctor::Bool(false), Ctor::Bool(true)],
+        Ty::Tuple { fields } => vec![Ctor::Tuple(fields.len())],
+        Ty::Adt { id, .. } => adts
+            .get(*id)
+            .unwrap()
+            .variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| Ctor::Variant {
+                adt: *id,
+                index,
+                arity: variant.fields.len(),
+            })
+            .collect(),
+        _ => return None,
+    };
+
+    Some(ctors)
+}
+
+/// A pattern where all the information that does not affect the matched values is removed.
+#[derive(Debug, Clone)]
+enum Pat {
+    /// A pattern that matches any value.
+    Wild,
+    /// A pattern that matches the values built with a constructor whose fields match the given
+    /// patterns.
+    Ctor(Ctor, Vec<Pat>),
+}
+
+impl From<&Pattern> for Pat {
+    fn from(pattern: &Pattern) -> Self {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Binding(_) => Self::Wild,
+            PatternKind::Literal(literal) => {
+                let ctor = match literal.base_ty() {
+                    BaseTy::Bool => Ctor::Bool(literal.bits() != 0),
+                    BaseTy::Int => Ctor::Int(literal.bits()),
+                };
+                Self::Ctor(ctor, Vec::new())
+            }
+            PatternKind::Constructor {
+                adt,
+                variant,
+                fields,
+            } => Self::Ctor(
+                Ctor::Variant {
+                    adt: *adt,
+                    index: *variant,
+                    arity: fields.len(),
+                },
+                fields.iter().map(Self::from).collect(),
+            ),
+            PatternKind::Tuple { fields } => Self::Ctor(
+                Ctor::Tuple(fields.len()),
+                fields.iter().map(Self::from).collect(),
+            ),
+        }
+    }
+}
+
+impl Show<Adts> for Pat {
+    fn show(&self, adts: &Adts, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wild => write!(f, "_"),
+            Self::Ctor(Ctor::Bool(boolean), _) => write!(f, "{}", boolean),
+            Self::Ctor(Ctor::Int(integer), _) => write!(f, "{}", integer),
+            Self::Ctor(Ctor::Tuple(_), fields) => {
+                write!(f, "({})", Show::<Adts>::show_sep(fields, ", ").wrap(adts))
+            }
+            Self::Ctor(Ctor::Variant { adt, index, .. }, fields) => {
+                let name = &adts.get(*adt).unwrap().variants[*index].name;
+
+                if fields.is_empty() {
+                    write!(f, "{}", name)
+                } else {
+                    let fields = Show::<Adts>::show_sep(fields, ", ");
+                    write!(f, "{}({})", name, fields.wrap(adts))
+                }
+            }
+        }
+    }
+}
+
+/// Specialize a row of patterns for a constructor.
+///
+/// Return `None` if the first pattern of the row cannot match values built with the constructor.
+/// Otherwise, the first pattern is replaced by the patterns for the fields of the constructor.
+fn specialize(row: &[Pat], ctor: Ctor) -> Option<Vec<Pat>> {
+    let (head, tail) = row.split_first()?;
+
+    let mut fields = match head {
+        Pat::Wild => vec![Pat::Wild; ctor.arity()],
+        Pat::Ctor(head_ctor, fields) if *head_ctor == ctor => fields.clone(),
+        Pat::Ctor(..) => return None,
+    };
+
+    fields.extend_from_slice(tail);
+    Some(fields)
+}
+
+/// Specialize every row of a matrix of patterns for a constructor.
+fn specialize_matrix(matrix: &[Vec<Pat>], ctor: Ctor) -> Vec<Vec<Pat>> {
+    matrix
+        .iter()
+        .filter_map(|row| specialize(row, ctor))
+        .collect()
+}
+
+/// Keep the rows of a matrix whose first pattern is a wildcard, without that pattern.
+fn default_matrix(matrix: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    matrix
+        .iter()
+        .filter_map(|row| match row.split_first()? {
+            (Pat::Wild, tail) => Some(tail.to_vec()),
+            (Pat::Ctor(..), _) => None,
+        })
+        .collect()
+}
+
+/// The constructors used by the first pattern of each row.
+fn head_ctors(matrix: &[Vec<Pat>]) -> Vec<Ctor> {
+    let mut ctors = Vec::new();
+
+    for row in matrix {
+        if let Some(Pat::Ctor(ctor, _)) = row.first() {
+            if !ctors.contains(ctor) {
+                ctors.push(*ctor);
+            }
+        }
+    }
+
+    ctors
+}
+
+/// Return all the constructors of a type if every one of them is used.
+fn complete_ctors(ty: &Ty, used: &[Ctor], adts: &Adts) -> Option<Vec<Ctor>> {
+    all_ctors(ty, adts).filter(|ctors| ctors.iter().all(|ctor| used.contains(ctor)))
+}
+
+/// Decide if a row of patterns is useful with respect to a matrix of patterns, where `tys` has
+/// the type of each column.
+fn is_useful(matrix: &[Vec<Pat>], row: &[Pat], tys: &[Ty], adts: &Adts) -> bool {
+    let (ty, tail_tys) = match tys.split_first() {
+        Some(split) => split,
+        // An empty row is useful only if there are no rows before it.
+        None => return matrix.is_empty(),
+    };
+
+    match &row[0] {
+        Pat::Ctor(ctor, _) => is_useful_ctor(matrix, row, *ctor, ty, tail_tys, adts),
+        Pat::Wild => match complete_ctors(ty, &head_ctors(matrix), adts) {
+            // A wildcard is useful if it is useful for any constructor.
+            Some(ctors) => ctors
+                .into_iter()
+                .any(|ctor| is_useful_ctor(matrix, row, ctor, ty, tail_tys, adts)),
+            // Otherwise, there are values that are only matched by the wildcards of the column.
+            None => is_useful(&default_matrix(matrix), &row[1..], tail_tys, adts),
+        },
+    }
+}
+
+/// Decide if a row of patterns is useful for the values built with a constructor.
+fn is_useful_ctor(
+    matrix: &[Vec<Pat>],
+    row: &[Pat],
+    ctor: Ctor,
+    ty: &Ty,
+    tail_tys: &[Ty],
+    adts: &Adts,
+) -> bool {
+    let mut tys = ctor.fields_ty(ty, adts);
+    tys.extend_from_slice(tail_tys);
+
+    match specialize(row, ctor) {
+        Some(row) => is_useful(&specialize_matrix(matrix, ctor), &row, &tys, adts),
+        None => false,
+    }
+}
+
+/// Find a row of patterns for values that are not matched by any row of a matrix of patterns,
+/// where `tys` has the type of each column.
+fn find_witness(matrix: &[Vec<Pat>], tys: &[Ty], adts: &Adts) -> Option<Vec<Pat>> {
+    let (ty, tail_tys) = match tys.split_first() {
+        Some(split) => split,
+        None if matrix.is_empty() => return Some(Vec::new()),
+        None => return None,
+    };
+
+    let used = head_ctors(matrix);
+
+    match complete_ctors(ty, &used, adts) {
+        Some(ctors) => ctors.into_iter().find_map(|ctor| {
+            let mut tys = ctor.fields_ty(ty, adts);
+            tys.extend_from_slice(tail_tys);
+
+            let mut witness = find_witness(&specialize_matrix(matrix, ctor), &tys, adts)?;
+            let fields = witness.drain(..ctor.arity()).collect();
+            witness.insert(0, Pat::Ctor(ctor, fields));
+
+            Some(witness)
+        }),
+        None => {
+            let mut witness = find_witness(&default_matrix(matrix), tail_tys, adts)?;
+
+            // Show a missing constructor if there is one and the other constructors are used.
+            // Otherwise, a wildcard is clearer.
+            let missing = all_ctors(ty, adts)
+                .filter(|_| !used.is_empty())
+                .and_then(|ctors| ctors.into_iter().find(|ctor| !used.contains(ctor)))
+                .map_or(Pat::Wild, |ctor| {
+                    Pat::Ctor(ctor, vec![Pat::Wild; ctor.arity()])
+                });
+            witness.insert(0, missing);
+
+            Some(witness)
+        }
+    }
+}
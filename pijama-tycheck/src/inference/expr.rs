@@ -1,20 +1,28 @@
-use crate::{checker::Checker, error::TyResult, inference::InferTy};
+use crate::{
+    checker::Checker,
+    constraint::Field,
+    error::{TyErrorKind, TyResult},
+    inference::InferTy,
+};
 
-use pijama_hir::{BinOp, Expr, ExprKind, UnOp};
+use pijama_hir::{Atom, BinOp, Expr, ExprKind, UnOp};
 use pijama_ty::{base::BaseTy, inference::Ty};
 
 impl InferTy for Expr {
     fn infer_ty(&self, checker: &mut Checker) -> TyResult<Ty> {
         let ty = match &self.kind {
-            // Infering the type of an atom is straightforward.
-            ExprKind::Atom(atom) => atom.infer_ty(checker)?,
+            // Infering the type of a literal is straightforward.
+            ExprKind::Atom(Atom::Literal(literal)) => literal.infer_ty(checker)?,
+            // The type of a name depends on where it is used because functions can be
+            // polymorphic.
+            ExprKind::Atom(Atom::Name(name)) => checker.get_name_ty(name, self.id).unwrap(),
             ExprKind::Let { lhs, rhs, body } => {
                 // Infer the types of both sides.
                 let lhs_ty = lhs.infer_ty(checker)?;
                 let rhs_ty = rhs.infer_ty(checker)?;
 
                 // Those types have to be equal.
-                checker.add_constraint(lhs_ty, rhs_ty);
+                checker.add_constraint(lhs_ty, rhs_ty, checker.expr_span(rhs.id));
 
                 // Then the type of this expression is the type of the body.
                 body.infer_ty(checker)?
@@ -24,7 +32,7 @@ impl InferTy for Expr {
                 // better or not.
 
                 // Infer the type of the called function.
-                let lhs_ty = checker.get_name_ty(func).unwrap().clone();
+                let lhs_ty = func.infer_ty(checker)?;
 
                 // Infer the type of ech argument of the call.
                 let params_ty = args
@@ -42,7 +50,7 @@ impl InferTy for Expr {
                 };
 
                 // The type of the called function must be equal to the type we just created.
-                checker.add_constraint(lhs_ty, rhs_ty);
+                checker.add_constraint(lhs_ty, rhs_ty, checker.expr_span(self.id));
 
                 // The type of a call is the return type of the function.
                 return_ty
@@ -58,7 +66,7 @@ impl InferTy for Expr {
 
                 // The operand must have the type that the operator expects.
                 let ty = op.infer_ty(checker)?;
-                checker.add_constraint(expected_ty, ty);
+                checker.add_constraint(expected_ty, ty, checker.expr_span(op.id));
 
                 // The type of this expression is the type that the operator returns.
                 infered_ty
@@ -88,8 +96,8 @@ impl InferTy for Expr {
                 let left_ty = left_op.infer_ty(checker)?;
                 let right_ty = right_op.infer_ty(checker)?;
 
-                checker.add_constraint(expected_ty.clone(), left_ty);
-                checker.add_constraint(expected_ty, right_ty);
+                checker.add_constraint(expected_ty.clone(), left_ty, checker.expr_span(left_op.id));
+                checker.add_constraint(expected_ty, right_ty, checker.expr_span(right_op.id));
 
                 // The type of this expression is the type that the operator returns.
                 infered_ty
@@ -99,15 +107,15 @@ impl InferTy for Expr {
                 do_branch,
                 else_branch,
             } => {
-                let cond = cond.infer_ty(checker)?;
+                let cond_ty = cond.infer_ty(checker)?;
                 let do_ty = do_branch.infer_ty(checker)?;
                 let else_ty = else_branch.infer_ty(checker)?;
 
                 // The type of the condition must be boolean.
-                checker.add_constraint(Ty::Base(BaseTy::Bool), cond);
+                checker.add_constraint(Ty::Base(BaseTy::Bool), cond_ty, checker.expr_span(cond.id));
 
                 // The type of both branches must be the same.
-                checker.add_constraint(do_ty.clone(), else_ty);
+                checker.add_constraint(do_ty.clone(), else_ty, checker.expr_span(else_branch.id));
 
                 // The type of this expression is the type of the branches.
                 do_ty
@@ -120,6 +128,138 @@ impl InferTy for Expr {
 
                 Ty::Tuple { fields }
             }
+            ExprKind::Projection { tuple, index } => {
+                let tuple_ty = tuple.infer_ty(checker)?;
+
+                // Create a new hole for the type of the field.
+                let field_ty = checker.tcx.new_ty();
+
+                // The tuple must have a field with this index and type.
+                checker.add_field_constraint(
+                    tuple_ty,
+                    Field::Index(*index),
+                    field_ty.clone(),
+                    checker.expr_span(tuple.id),
+                );
+
+                field_ty
+            }
+            ExprKind::Record { ty, fields } => {
+                for (pos, field) in fields {
+                    let field_ty = field.infer_ty(checker)?;
+
+                    // Each field must have the type it was declared with.
+                    if let Ty::Record {
+                        fields: decl_fields,
+                        ..
+                    } = ty.unaliased()
+                    {
+                        let decl_ty = decl_fields[*pos].1.clone();
+                        checker.add_constraint(decl_ty, field_ty, checker.expr_span(field.id));
+                    }
+                }
+
+                ty.clone()
+            }
+            ExprKind::Field { record, name } => {
+                let record_ty = record.infer_ty(checker)?;
+
+                // Create a new hole for the type of the field.
+                let field_ty = checker.tcx.new_ty();
+
+                // The record must have a field with this name and type.
+                checker.add_field_constraint(
+                    record_ty,
+                    Field::Name(name.clone()),
+                    field_ty.clone(),
+                    checker.expr_span(record.id),
+                );
+
+                field_ty
+            }
+            ExprKind::Update { record, fields } => {
+                let record_ty = record.infer_ty(checker)?;
+
+                // The record must have each updated field and the new value must have the type of
+                // the field.
+                for (name, field) in fields {
+                    let field_ty = field.infer_ty(checker)?;
+
+                    checker.add_field_constraint(
+                        record_ty.clone(),
+                        Field::Name(name.clone()),
+                        field_ty,
+                        checker.expr_span(field.id),
+                    );
+                }
+
+                // The updated record has the same type.
+                record_ty
+            }
+            ExprKind::Construct { adt, variant, args } => {
+                let fields_ty = &checker.get_variant(*adt, *variant).fields;
+
+                // Error if the number of arguments does not match the number of fields.
+                if fields_ty.len() != args.len() {
+                    return Err(TyErrorKind::ArityMismatch {
+                        expected: fields_ty.len(),
+                        found: args.len(),
+                    }
+                    .into_err(checker.expr_span(self.id)));
+                }
+
+                // Each argument must have the type of its field.
+                for (field_ty, arg) in fields_ty.iter().zip(args) {
+                    let arg_ty = arg.infer_ty(checker)?;
+                    checker.add_constraint(field_ty.clone(), arg_ty, checker.expr_span(arg.id));
+                }
+
+                checker.get_adt_ty(*adt)
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let scrutinee_ty = scrutinee.infer_ty(checker)?;
+
+                // Create a new hole for the type of the arms.
+                let ty = checker.tcx.new_ty();
+
+                for arm in arms {
+                    // Each pattern must have the type of the scrutinee.
+                    let pattern_ty = arm.pattern.infer_ty(checker)?;
+                    checker.add_constraint(scrutinee_ty.clone(), pattern_ty, arm.pattern.span);
+
+                    // The type of every arm must be the same.
+                    let body_ty = arm.body.infer_ty(checker)?;
+                    checker.add_constraint(ty.clone(), body_ty, checker.expr_span(arm.body.id));
+                }
+
+                // The patterns are checked once the type of the scrutinee is known.
+                let patterns = arms.iter().map(|arm| arm.pattern.clone()).collect();
+                checker.add_match(scrutinee_ty, patterns, checker.expr_span(self.id));
+
+                ty
+            }
+            ExprKind::Lambda {
+                params,
+                return_ty,
+                body,
+            } => {
+                let params_ty = params
+                    .iter()
+                    .map(|param| param.infer_ty(checker))
+                    .collect::<TyResult<Vec<Ty>>>()?;
+
+                // The type of the body must be equal to the return type of the function.
+                let body_ty = body.infer_ty(checker)?;
+                checker.add_constraint(return_ty.clone(), body_ty, checker.expr_span(body.id));
+
+                Ty::Func {
+                    params_ty,
+                    return_ty: Box::new(return_ty.clone()),
+                }
+            }
+            ExprKind::Instantiate { func, ty_args } => {
+                checker.get_func_ty_with(*func, ty_args, self.id)?
+            }
         };
 
         // Store the infered type for the expression.
@@ -1,7 +1,7 @@
-mod atom;
 mod expr;
 mod literal;
 mod local;
+mod pattern;
 
 use crate::{checker::Checker, error::TyResult};
 
@@ -0,0 +1,54 @@
+use crate::{
+    checker::Checker,
+    error::{TyErrorKind, TyResult},
+    inference::InferTy,
+};
+
+use pijama_hir::{Pattern, PatternKind};
+use pijama_ty::inference::Ty;
+
+impl InferTy for Pattern {
+    fn infer_ty(&self, checker: &mut Checker) -> TyResult<Ty> {
+        let ty = match &self.kind {
+            // A wildcard can match a value of any type.
+            PatternKind::Wildcard => checker.tcx.new_ty(),
+            // A binding matches values with the type of its local.
+            PatternKind::Binding(local) => local.infer_ty(checker)?,
+            PatternKind::Literal(literal) => literal.infer_ty(checker)?,
+            PatternKind::Constructor {
+                adt,
+                variant,
+                fields,
+            } => {
+                let fields_ty = &checker.get_variant(*adt, *variant).fields;
+
+                // Error if the number of patterns does not match the number of fields.
+                if fields_ty.len() != fields.len() {
+                    return Err(TyErrorKind::ArityMismatch {
+                        expected: fields_ty.len(),
+                        found: fields.len(),
+                    }
+                    .into_err(self.span));
+                }
+
+                // Each pattern must have the type of its field.
+                for (field_ty, field) in fields_ty.iter().zip(fields) {
+                    let ty = field.infer_ty(checker)?;
+                    checker.add_constraint(field_ty.clone(), ty, field.span);
+                }
+
+                checker.get_adt_ty(*adt)
+            }
+            PatternKind::Tuple { fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|field| field.infer_ty(checker))
+                    .collect::<TyResult<Vec<_>>>()?;
+
+                Ty::Tuple { fields }
+            }
+        };
+
+        Ok(ty)
+    }
+}
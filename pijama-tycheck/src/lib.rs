@@ -1,18 +1,31 @@
+mod call_graph;
 mod checker;
 mod constraint;
 pub mod error;
+mod exhaustiveness;
 mod inference;
+mod scheme;
 mod substitution;
 mod table;
 mod unifier;
+mod union_find;
 
 use checker::Checker;
-use error::TyResult;
+use error::TyError;
 use pijama_hir::Program;
 use pijama_ty::inference::TyContext;
-pub use table::Table;
+pub use scheme::Scheme;
+pub use table::{Table, TableBuilder};
 pub use unifier::Unifier;
 
-pub fn check_program(tcx: &TyContext, program: &Program) -> TyResult<(Unifier, Table)> {
-    Checker::new(tcx).check_program(program)
+/// Type-check a program.
+///
+/// If the program is well-typed, return an [Unifier] and the types of its expressions, which can
+/// be used to instantiate the type of any expression for each instance of its function.
+/// Otherwise, return all the type errors found.
+pub fn check_program(
+    tcx: &TyContext,
+    program: &Program,
+) -> Result<(Unifier, TableBuilder), Vec<TyError>> {
+    Checker::new(tcx, &program.spans, &program.adts).check_program(program)
 }
@@ -0,0 +1,66 @@
+use crate::substitution::Substitution;
+
+use pijama_ty::inference::{Ty, TyContext, TyVar};
+
+/// A type scheme.
+///
+/// This is the type of a top-level function after type-checking. Every variable in `vars` is
+/// universally quantified, which means that it can be replaced by a different type each time the
+/// function is used.
+#[derive(Debug, Clone)]
+pub struct Scheme {
+    /// The quantified type variables.
+    pub vars: Vec<TyVar>,
+    /// The type of the function.
+    pub ty: Ty,
+}
+
+impl Scheme {
+    /// Create a new scheme that quantifies the variables of the type parameters of a function
+    /// and all the type variables of its type, except the ones in `fixed`.
+    ///
+    /// The variables of the type parameters are the first quantified variables, in the same
+    /// order.
+    pub(crate) fn generalize(ty: Ty, params: &[Ty], fixed: &[TyVar]) -> Self {
+        let mut vars: Vec<TyVar> = params
+            .iter()
+            .map(|param| match param {
+                Ty::Param { var, .. } => *var,
+                _ => unreachable!("Type parameters should be rigid variables."),
+            })
+            .collect();
+        vars.extend(
+            ty.free_vars()
+                .into_iter()
+                .filter(|var| !fixed.contains(var)),
+        );
+
+        Self { vars, ty }
+    }
+
+    /// Create a new scheme without quantified type variables.
+    pub(crate) fn mono(ty: Ty) -> Self {
+        Self {
+            vars: Vec::new(),
+            ty,
+        }
+    }
+
+    /// Replace each quantified variable by a new type variable, except for the first ones, which
+    /// are replaced by the types in `args` in order.
+    ///
+    /// Return the instantiated type and the type that replaced each quantified variable.
+    pub(crate) fn instantiate(&self, tcx: &TyContext, args: &[Ty]) -> (Ty, Vec<Ty>) {
+        let mut ty = self.ty.clone();
+        let mut args = args.iter();
+        let mut instance_args = Vec::with_capacity(self.vars.len());
+
+        for var in &self.vars {
+            let arg = args.next().cloned().unwrap_or_else(|| tcx.new_ty());
+            Substitution::new(*var, arg.clone()).apply_to(&mut ty);
+            instance_args.push(arg);
+        }
+
+        (ty, instance_args)
+    }
+}
@@ -23,7 +23,7 @@ impl Substitution {
     pub(crate) fn apply_to(&self, ty: &mut Ty) {
         match ty {
             // Change the type to the `output` field if the type matches the `input` field.
-            Ty::Var(var) if *var == self.input => *ty = self.output.clone(),
+            Ty::Var(var) | Ty::Param { var, .. } if *var == self.input => *ty = self.output.clone(),
             // if the type is a function, apply this substitution recursively on the parameters and
             // return types.
             Ty::Func {
@@ -41,13 +41,24 @@ impl Substitution {
                     self.apply_to(ty);
                 }
             }
+            // If the type is a record, apply this substitution recursively on the fields.
+            Ty::Record { fields, .. } => {
+                for (_, ty) in fields {
+                    self.apply_to(ty);
+                }
+            }
+            // If the type is an alias, apply this substitution on the type it stands for.
+            Ty::Alias { ty, .. } => self.apply_to(ty.as_mut()),
             // Otherwise, left the type as it is.
-            Ty::Var(_) | Ty::Base(_) => (),
+            Ty::Var(_) | Ty::Param { .. } | Ty::Base(_) | Ty::Adt { .. } | Ty::Error => (),
         }
     }
 }
 
-impl<Ctx> Show<Ctx> for Substitution {
+impl<Ctx> Show<Ctx> for Substitution
+where
+    TyVar: Show<Ctx>,
+{
     fn show(&self, ctx: &Ctx, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{} -> {}", self.input.wrap(ctx), self.output.wrap(ctx))
     }
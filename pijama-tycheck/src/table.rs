@@ -1,5 +1,3 @@
-use crate::Unifier;
-
 use pijama_ty::{inference, ty, ExprId};
 use pijama_utils::index::IndexMap;
 
@@ -8,6 +6,13 @@ pub struct Table {
 }
 
 impl Table {
+    /// Create a new and empty table.
+    pub fn new() -> Self {
+        Self {
+            types: IndexMap::new(),
+        }
+    }
+
     pub fn store_ty(&mut self, ty: ty::Ty) -> ExprId {
         self.types.insert(ty)
     }
@@ -23,6 +28,16 @@ impl Table {
     }
 }
 
+impl Default for Table {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The types of the expressions of a program before instantiating them.
+///
+/// The types of the expressions inside polymorphic functions can only be turned into concrete
+/// types by the [crate::Unifier] after choosing an instance of the function.
 pub struct TableBuilder {
     types: IndexMap<ExprId, Option<inference::Ty>>,
 }
@@ -35,21 +50,4 @@ impl TableBuilder {
     pub fn get_ty(&self, expr_id: ExprId) -> Option<&inference::Ty> {
         self.types.get(expr_id).and_then(|ty| ty.as_ref())
     }
-
-    pub fn build(self, unifier: &Unifier) -> Result<Table, ExprId> {
-        let types = IndexMap::from_raw(
-            self.types
-                .into_iter()
-                .map(|(expr_id, ty)| {
-                    if let Some(ty) = ty {
-                        Ok(unifier.instantiate(ty))
-                    } else {
-                        Err(expr_id)
-                    }
-                })
-                .collect::<Result<Vec<ty::Ty>, ExprId>>()?,
-        );
-
-        Ok(Table { types })
-    }
 }
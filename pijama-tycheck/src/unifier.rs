@@ -1,19 +1,27 @@
-use std::collections::BTreeMap;
-
-use crate::{
-    error::{TyError, TyResult},
-    substitution::Substitution,
-};
+use crate::{scheme::Scheme, union_find::UnionFind};
 
+use pijama_hir::FuncId;
 use pijama_ty::{
     inference::{self, TyVar},
-    ty,
+    ty, ExprId,
 };
+use pijama_utils::index::IndexMap;
+
+use std::collections::{BTreeMap, HashMap};
 
 /// A type to replace all inference variables by concrete types, if possible.
+///
+/// Polymorphic functions have type variables that can only be replaced by concrete types after
+/// choosing a type for each one of their quantified variables.
 #[derive(Debug)]
 pub struct Unifier {
-    substitutions: BTreeMap<TyVar, ty::Ty>,
+    /// The type variables that are known to be equal and their types.
+    vars: UnionFind,
+    /// The type scheme of each function.
+    schemes: IndexMap<FuncId, Scheme>,
+    /// The types that replace the quantified variables of each function at each expression
+    /// where it is used.
+    instances: HashMap<ExprId, Vec<inference::Ty>>,
 }
 
 impl Unifier {
@@ -26,12 +34,28 @@ impl Unifier {
     ///
     /// This function panics if the type has inference variables that are not in the unifier.
     pub fn instantiate(&self, ty: inference::Ty) -> ty::Ty {
+        self.instantiate_with(ty, &BTreeMap::new())
+    }
+
+    /// Apply all the substitutions to a type in order to instantiate it, using `args` to replace
+    /// the quantified variables of a polymorphic function.
+    ///
+    /// This function panics if the type has inference variables that are neither in the unifier
+    /// nor in `args`.
+    pub fn instantiate_with(&self, ty: inference::Ty, args: &BTreeMap<TyVar, ty::Ty>) -> ty::Ty {
         match ty {
             // Change the type to the `output` if the type matches the `input`.
-            inference::Ty::Var(var) => self
-                .substitutions
+            inference::Ty::Var(var) => match self.vars.get_ty(var) {
+                Some(ty) => self.instantiate_with(ty.clone(), args),
+                None => args
+                    .get(&self.vars.find_root(var))
+                    .expect("Every type variable should have a substitution")
+                    .clone(),
+            },
+            // Type parameters are replaced by the type arguments of the function.
+            inference::Ty::Param { var, .. } => args
                 .get(&var)
-                .expect("Every type variable should have a substitution")
+                .expect("Every type parameter should have a substitution")
                 .clone(),
             // if the type is a function, apply the substitutions recursively on the parameters and
             // return types.
@@ -41,40 +65,97 @@ impl Unifier {
             } => ty::Ty::Func {
                 params_ty: params_ty
                     .into_iter()
-                    .map(|ty| self.instantiate(ty))
+                    .map(|ty| self.instantiate_with(ty, args))
                     .collect(),
-                return_ty: Box::new(self.instantiate(*return_ty)),
+                return_ty: Box::new(self.instantiate_with(*return_ty, args)),
             },
             // if the type is a tuple, apply the substitutions recursively on the fields.
             inference::Ty::Tuple { fields } => ty::Ty::Tuple {
-                fields: fields.into_iter().map(|ty| self.instantiate(ty)).collect(),
+                fields: fields
+                    .into_iter()
+                    .map(|ty| self.instantiate_with(ty, args))
+                    .collect(),
+            },
+            // If the type is a record, apply the substitutions recursively on the fields.
+            inference::Ty::Record { name, fields } => ty::Ty::Record {
+                name,
+                fields: fields
+                    .into_iter()
+                    .map(|(field, ty)| (field, self.instantiate_with(ty, args)))
+                    .collect(),
             },
+            // If the type is an alias, instantiate the type it stands for.
+            inference::Ty::Alias { ty, .. } => self.instantiate_with(*ty, args),
             // Otherwise, left the type as it is.
             inference::Ty::Base(base) => ty::Ty::Base(base),
+            inference::Ty::Adt { id, name } => ty::Ty::Adt { id, name },
+            inference::Ty::Error => panic!("Error types cannot be instantiated."),
         }
     }
+
+    /// Get the type scheme of a function.
+    pub fn scheme(&self, func_id: FuncId) -> &Scheme {
+        self.schemes
+            .get(func_id)
+            .expect("Every function should have a scheme.")
+    }
+
+    /// Get the types that replace the quantified variables of the function used by an
+    /// expression, if the expression uses a function.
+    ///
+    /// The types are in the same order as the quantified variables of the function's scheme.
+    pub fn instance_args(&self, expr_id: ExprId) -> Option<&[inference::Ty]> {
+        self.instances.get(&expr_id).map(Vec::as_slice)
+    }
 }
 
 /// An unifier builder.
+///
+/// The builder keeps track of the type variables that are known to be equal and of the types
+/// bound to them using a union-find table.
 pub(crate) struct UnifierBuilder {
-    substitutions: BTreeMap<TyVar, inference::Ty>,
+    vars: UnionFind,
 }
 
 impl UnifierBuilder {
     /// Create a new and empty unifier builder.
     fn new() -> Self {
         Self {
-            substitutions: BTreeMap::new(),
+            vars: UnionFind::new(),
+        }
+    }
+
+    /// Replace a type variable by the type bound to it, if any, or by the representative of its
+    /// set otherwise.
+    ///
+    /// Only the outermost type is resolved. The types inside functions and tuples are left as
+    /// they are.
+    pub(crate) fn shallow_resolve(&mut self, ty: inference::Ty) -> inference::Ty {
+        match ty {
+            inference::Ty::Var(var) => {
+                let root = self.vars.find(var);
+                match self.vars.get_ty(root) {
+                    // The bound type can be a variable from a set that was bound later.
+                    Some(ty) => {
+                        let ty = ty.clone();
+                        self.shallow_resolve(ty)
+                    }
+                    None => inference::Ty::Var(root),
+                }
+            }
+            ty => ty,
         }
     }
 
-    /// Apply in-place all the substitutions to a type.
-    fn apply_substitutions(&self, ty: &mut inference::Ty) {
+    /// Replace in-place every type variable inside a type by the type bound to it, if any, or by
+    /// the representative of its set otherwise.
+    pub(crate) fn apply_substitutions(&mut self, ty: &mut inference::Ty) {
         match ty {
             inference::Ty::Var(var) => {
-                // Change the type to the `output` if the type matches the `input`.
-                if let Some(output) = self.substitutions.get(var) {
-                    *ty = output.clone();
+                *ty = self.shallow_resolve(inference::Ty::Var(*var));
+                // Keep replacing variables inside the new type.
+                if !matches!(ty, inference::Ty::Var(_)) {
+                    self.apply_substitutions(ty);
                 }
             }
             // if the type is a function, apply the substitutions recursively on the parameters and
@@ -94,62 +175,83 @@ impl UnifierBuilder {
                     self.apply_substitutions(ty);
                 }
             }
+            // If the type is a record, apply the substitutions recursively on the fields.
+            inference::Ty::Record { fields, .. } => {
+                for (_, ty) in fields {
+                    self.apply_substitutions(ty);
+                }
+            }
+            // If the type is an alias, apply the substitutions on the type it stands for.
+            inference::Ty::Alias { ty, .. } => self.apply_substitutions(ty.as_mut()),
             // Otherwise, left the type as it is.
-            inference::Ty::Base(_) => (),
+            inference::Ty::Base(_)
+            | inference::Ty::Param { .. }
+            | inference::Ty::Adt { .. }
+            | inference::Ty::Error => (),
         }
     }
 
-    /// Add a substitution to the unifier.
-    pub(crate) fn add_substitution(&mut self, mut substitution: Substitution) {
-        // Compose the new substitution with all the already used substitutions.
-        self.apply_substitutions(&mut substitution.output);
-        // Push the substitution into the substitutions list.
-        self.substitutions
-            .insert(substitution.input, substitution.output);
+    /// Check if a type variable appears inside a type after replacing the variables bound to
+    /// other types.
+    pub(crate) fn occurs(&mut self, var: TyVar, ty: &inference::Ty) -> bool {
+        match ty {
+            inference::Ty::Var(other) => match self.shallow_resolve(inference::Ty::Var(*other)) {
+                inference::Ty::Var(root) => self.vars.find(var) == root,
+                ty => self.occurs(var, &ty),
+            },
+            inference::Ty::Func {
+                params_ty,
+                return_ty,
+            } => params_ty.iter().any(|ty| self.occurs(var, ty)) || self.occurs(var, return_ty),
+            inference::Ty::Tuple { fields } => fields.iter().any(|ty| self.occurs(var, ty)),
+            inference::Ty::Record { fields, .. } => {
+                fields.iter().any(|(_, ty)| self.occurs(var, ty))
+            }
+            inference::Ty::Alias { ty, .. } => self.occurs(var, ty),
+            inference::Ty::Base(_)
+            | inference::Ty::Param { .. }
+            | inference::Ty::Adt { .. }
+            | inference::Ty::Error => false,
+        }
     }
 
-    /// Consume this builder and try to create an unifier.
+    /// Bind every unbound type variable inside a type to the error type.
     ///
-    /// This fails if any of the substitution's output has inference varaibles in it.
-    pub(crate) fn build(self) -> TyResult<Unifier> {
-        let mut substitutions = BTreeMap::new();
+    /// This is used after finding an error so the variables involved in it do not cause more
+    /// errors.
+    pub(crate) fn poison(&mut self, ty: &inference::Ty) {
+        let mut ty = ty.clone();
+        self.apply_substitutions(&mut ty);
 
-        for (input, output) in self.substitutions {
-            let output = try_concrete(output)?;
-            substitutions.insert(input, output);
+        for var in ty.free_vars() {
+            self.bind(var, inference::Ty::Error);
         }
-        Ok(Unifier { substitutions })
     }
-}
 
-/// Try to convert an inference type into a concrete type without holes. Error with
-/// the first `HoleId` found otherwise.
-fn try_concrete(ty: inference::Ty) -> TyResult<ty::Ty> {
-    match ty {
-        inference::Ty::Base(base) => Ok(ty::Ty::Base(base)),
-        inference::Ty::Var(var) => Err(TyError::FoundVar(var)),
-        inference::Ty::Func {
-            params_ty,
-            return_ty,
-        } => {
-            let params_ty = params_ty
-                .into_iter()
-                .map(try_concrete)
-                .collect::<TyResult<Vec<ty::Ty>>>()?;
-            let return_ty = Box::new(try_concrete(*return_ty)?);
-
-            Ok(ty::Ty::Func {
-                params_ty,
-                return_ty,
-            })
-        }
-        inference::Ty::Tuple { fields } => {
-            let fields = fields
-                .into_iter()
-                .map(try_concrete)
-                .collect::<TyResult<Vec<ty::Ty>>>()?;
+    /// Record that two unbound type variables are equal.
+    pub(crate) fn union(&mut self, var1: TyVar, var2: TyVar) {
+        self.vars.union(var1, var2);
+    }
+
+    /// Record that an unbound type variable is equal to a type.
+    pub(crate) fn bind(&mut self, var: TyVar, ty: inference::Ty) {
+        self.vars.bind(var, ty);
+    }
+
+    /// Consume this builder and create an unifier using the type schemes of the functions and
+    /// the types that replace their quantified variables at each use.
+    pub(crate) fn build(
+        mut self,
+        schemes: IndexMap<FuncId, Scheme>,
+        instances: HashMap<ExprId, Vec<inference::Ty>>,
+    ) -> Unifier {
+        // Compress the table so the unifier can find the representatives faster.
+        self.vars.compress();
 
-            Ok(ty::Ty::Tuple { fields })
+        Unifier {
+            vars: self.vars,
+            schemes,
+            instances,
         }
     }
 }
@@ -0,0 +1,128 @@
+use pijama_ty::inference::{Ty, TyVar};
+use pijama_utils::index::Index;
+
+/// An entry of the union-find table.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// The parent of the variable. A variable is the representative of its set if it is its own
+    /// parent.
+    parent: TyVar,
+    /// An upper bound for the height of the tree rooted at this variable.
+    rank: usize,
+    /// The type bound to the set of this variable. Only representatives use this field.
+    ty: Option<Ty>,
+}
+
+/// A union-find table of type variables.
+///
+/// Each set in the table is a group of type variables that are known to be equal, the set can be
+/// bound to a type if the variables are also known to be equal to that type. Type variables that
+/// have not been added to the table are in their own set and are not bound to any type.
+#[derive(Debug, Default)]
+pub(crate) struct UnionFind {
+    entries: Vec<Entry>,
+}
+
+impl UnionFind {
+    /// Create a new and empty table.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make sure that a variable is in the table.
+    fn insert(&mut self, var: TyVar) {
+        for index in self.entries.len()..=var.index() {
+            self.entries.push(Entry {
+                parent: TyVar::new(index),
+                rank: 0,
+                ty: None,
+            });
+        }
+    }
+
+    /// Find the representative of the set of a variable, compressing the path to it.
+    pub(crate) fn find(&mut self, var: TyVar) -> TyVar {
+        let root = self.find_root(var);
+
+        // Make every variable in the path point directly to the representative.
+        let mut current = var;
+        while let Some(entry) = self.entries.get_mut(current.index()) {
+            if entry.parent == root {
+                break;
+            }
+            current = std::mem::replace(&mut entry.parent, root);
+        }
+
+        root
+    }
+
+    /// Find the representative of the set of a variable without compressing the path to it.
+    pub(crate) fn find_root(&self, mut var: TyVar) -> TyVar {
+        while let Some(entry) = self.entries.get(var.index()) {
+            if entry.parent == var {
+                break;
+            }
+            var = entry.parent;
+        }
+
+        var
+    }
+
+    /// Get the type bound to the set of a variable, if any.
+    pub(crate) fn get_ty(&self, var: TyVar) -> Option<&Ty> {
+        let root = self.find_root(var);
+        self.entries.get(root.index())?.ty.as_ref()
+    }
+
+    /// Bind the set of a variable to a type.
+    ///
+    /// This function panics if the set was already bound to a type.
+    pub(crate) fn bind(&mut self, var: TyVar, ty: Ty) {
+        let root = self.find(var);
+        self.insert(root);
+
+        let entry = &mut self.entries[root.index()];
+        assert!(entry.ty.is_none(), "Sets cannot be bound twice.");
+        entry.ty = Some(ty);
+    }
+
+    /// Merge the sets of two variables that are not bound to a type.
+    ///
+    /// This function panics if any of the sets was already bound to a type.
+    pub(crate) fn union(&mut self, var1: TyVar, var2: TyVar) {
+        let root1 = self.find(var1);
+        let root2 = self.find(var2);
+
+        if root1 == root2 {
+            return;
+        }
+
+        self.insert(root1);
+        self.insert(root2);
+
+        assert!(
+            self.entries[root1.index()].ty.is_none() && self.entries[root2.index()].ty.is_none(),
+            "Bound sets cannot be merged."
+        );
+
+        // The root of the shortest tree becomes a child of the root of the tallest one.
+        let rank1 = self.entries[root1.index()].rank;
+        let rank2 = self.entries[root2.index()].rank;
+
+        if rank1 < rank2 {
+            self.entries[root1.index()].parent = root2;
+        } else {
+            self.entries[root2.index()].parent = root1;
+            if rank1 == rank2 {
+                self.entries[root1.index()].rank += 1;
+            }
+        }
+    }
+
+    /// Compress the path of every variable in the table.
+    pub(crate) fn compress(&mut self) {
+        for index in 0..self.entries.len() {
+            self.find(TyVar::new(index));
+        }
+    }
+}
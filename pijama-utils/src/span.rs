@@ -54,6 +54,32 @@ impl Span {
         Self::new(self.end, self.end)
     }
 
+    /// Find the line and column (both starting at one) of the `start` of the current span in the
+    /// source code.
+    pub fn position(&self, source: &str) -> (usize, usize) {
+        let offset = self.start.min(source.len());
+        let (line_start, _) = self.line_bounds(source);
+
+        let line = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..offset].chars().count() + 1;
+
+        (line, column)
+    }
+
+    /// Find the byte range of the line containing the `start` of the current span in the source
+    /// code, without its newline character.
+    pub fn line_bounds(&self, source: &str) -> (usize, usize) {
+        // Offsets past the end of the file point to its last character.
+        let offset = self.start.min(source.len());
+
+        let line_start = source[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |pos| offset + pos);
+
+        (line_start, line_end)
+    }
+
     /// Join two spans by taking the smallest `start` between the two as the `start` and the
     /// largest `end` as the `end` of the new span.
     pub fn join(self, rhs: Self) -> Self {
@@ -1,21 +1,73 @@
-use pijama_interface::{Compiler, Config};
+use pijama_interface::{Compiler, Config, Emit, Status};
 
-use std::env::args;
+use std::{env::args, process::exit};
 
 fn main() {
-    // Get the path of the file with the source code.
-    let mut args = args();
-    args.next().unwrap();
-    let path = args.next().unwrap();
+    let mut args = args().skip(1).peekable();
+
+    // Run the program just in time if the first argument is the `run` command.
+    let run = args.next_if(|arg| arg == "run").is_some();
+
+    let mut path = None;
+    let mut print_signatures = false;
+    let mut checked = true;
+    let mut interpret = false;
+    let mut emit = Emit::Object;
+
+    // Get the path of the file with the source code and the flags.
+    for arg in args {
+        match arg.as_str() {
+            "--print-signatures" => print_signatures = true,
+            "--release" => checked = false,
+            "--interpret" if run => interpret = true,
+            "--emit=obj" => emit = Emit::Object,
+            "--emit=asm" => emit = Emit::Assembly,
+            "--emit=llvm-bc" => emit = Emit::Bitcode,
+            "--emit=llvm-ir" => emit = Emit::Ir,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None => usage(),
+    };
 
     // Create configuration.
     let config = Config {
         path: path.into(),
         codegen: true,
+        emit,
+        run,
+        interpret,
+        print_signatures,
+        checked,
     };
 
     env_logger::init();
 
-    // Run the compiler.
-    Compiler::new().run(config);
+    // Run the compiler and report any errors.
+    match Compiler::new().run(config) {
+        Ok(Status::Compiled) => (),
+        Ok(Status::Returned(value)) => println!("{}", value),
+        // Runtime errors exit in the same way as compiled programs that panic.
+        Ok(Status::Failed(error)) => {
+            eprintln!("{}", error);
+            exit(101);
+        }
+        Err(diagnostics) => {
+            eprintln!("{}", diagnostics);
+            exit(1);
+        }
+    }
+}
+
+/// Print how to use the compiler and exit.
+fn usage() -> ! {
+    eprintln!(
+        "usage: pijama [run [--interpret]] [--print-signatures] [--release] \
+        [--emit=obj|asm|llvm-bc|llvm-ir] <path>"
+    );
+    exit(1);
 }
@@ -0,0 +1,392 @@
+//! A generator of random well-typed programs.
+//!
+//! The generated programs do not use recursion so they always terminate. However, they might
+//! panic because of an overflow or a division by zero.
+
+use std::fmt::{self, Display, Write};
+
+/// A pseudo-random number generator using the xorshift algorithm.
+///
+/// It is used instead of an external crate so the programs generated for a seed never change.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // The state of the generator cannot be zero.
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Get a number between zero and `n`, excluding `n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Returns true with a probability of one in `n`.
+    fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    /// Choose an element of a slice.
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// The types used by the generated programs.
+#[derive(Clone, PartialEq)]
+enum Ty {
+    Int,
+    Bool,
+    Tuple(Vec<Ty>),
+    Func(Vec<Ty>, Box<Ty>),
+}
+
+impl Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ty::Int => write!(f, "Int"),
+            Ty::Bool => write!(f, "Bool"),
+            Ty::Tuple(fields) => write!(f, "({})", sep(fields)),
+            Ty::Func(params, return_ty) => write!(f, "fn({}) -> {}", sep(params), return_ty),
+        }
+    }
+}
+
+/// Display the elements of a slice separated by commas.
+fn sep<T: Display>(items: &[T]) -> String {
+    let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+    items.join(", ")
+}
+
+/// The signature of a generated function.
+struct Signature {
+    params: Vec<Ty>,
+    return_ty: Ty,
+}
+
+/// The state of the generator.
+struct Generator {
+    rng: Rng,
+    /// The signatures of the functions that can be called.
+    funcs: Vec<Signature>,
+    /// The locals in scope and their types.
+    locals: Vec<(usize, Ty)>,
+    /// The number of locals created so far, used to give each local a unique name.
+    next_local: usize,
+}
+
+/// The maximum depth of the expressions of a function's body.
+const MAX_DEPTH: usize = 4;
+
+/// Generate a random program from a seed.
+///
+/// The `main` function of the program returns an integer or a boolean.
+pub fn program(seed: u64) -> String {
+    let mut gen = Generator {
+        rng: Rng::new(seed),
+        funcs: Vec::new(),
+        locals: Vec::new(),
+        next_local: 0,
+    };
+
+    let mut source = String::new();
+
+    // Each function can only call the functions defined before it.
+    for index in 0..2 + gen.rng.below(4) {
+        let params = (0..gen.rng.below(4)).map(|_| gen.ty(2)).collect();
+        let return_ty = gen.ty(2);
+        let signature = Signature { params, return_ty };
+
+        source.push_str(&gen.func(index, &signature));
+        gen.funcs.push(signature);
+    }
+
+    // Call every function from `main` to make sure all of them are used.
+    gen.locals.clear();
+    let mut body = String::new();
+    for index in 0..gen.funcs.len() {
+        let return_ty = gen.funcs[index].return_ty.clone();
+        let call = gen.call(index, MAX_DEPTH - 1);
+        let local = gen.new_local(return_ty.clone());
+        write!(body, "let x{}: {} = {}; ", local, return_ty, call).unwrap();
+    }
+    let return_ty = gen.rng.choose(&[Ty::Int, Ty::Bool]).clone();
+    body.push_str(&gen.expr(&return_ty, MAX_DEPTH));
+
+    writeln!(source, "fn main(): {} do\n    {}\nend", return_ty, body).unwrap();
+
+    source
+}
+
+impl Generator {
+    /// Generate a random type with nested types up to the given depth.
+    fn ty(&mut self, depth: usize) -> Ty {
+        match self.rng.below(if depth == 0 { 2 } else { 6 }) {
+            0 | 2 => Ty::Int,
+            1 => Ty::Bool,
+            3 | 4 => Ty::Tuple(
+                (0..2 + self.rng.below(2))
+                    .map(|_| self.ty(depth - 1))
+                    .collect(),
+            ),
+            _ => {
+                let params = (0..1 + self.rng.below(2))
+                    .map(|_| self.ty(depth - 1))
+                    .collect();
+                Ty::Func(params, Box::new(self.ty(depth - 1)))
+            }
+        }
+    }
+
+    /// Create a new local of the given type and return its number.
+    fn new_local(&mut self, ty: Ty) -> usize {
+        let local = self.next_local;
+        self.next_local += 1;
+        self.locals.push((local, ty));
+        local
+    }
+
+    /// Generate the definition of a function.
+    fn func(&mut self, index: usize, signature: &Signature) -> String {
+        self.locals.clear();
+
+        let params: Vec<String> = signature
+            .params
+            .iter()
+            .map(|ty| format!("x{}: {}", self.new_local(ty.clone()), ty))
+            .collect();
+
+        let body = self.expr(&signature.return_ty, MAX_DEPTH);
+
+        format!(
+            "fn f{}({}): {} do\n    {}\nend\n\n",
+            index,
+            params.join(", "),
+            signature.return_ty,
+            body
+        )
+    }
+
+    /// Generate a call to a function with random arguments.
+    fn call(&mut self, index: usize, depth: usize) -> String {
+        let params = self.funcs[index].params.clone();
+        let args: Vec<String> = params.iter().map(|ty| self.expr(ty, depth)).collect();
+        format!("f{}({})", index, args.join(", "))
+    }
+
+    /// Generate an expression of the given type with nested expressions up to the given depth.
+    fn expr(&mut self, ty: &Ty, depth: usize) -> String {
+        if depth == 0 {
+            return self.leaf(ty);
+        }
+
+        let depth = depth - 1;
+
+        match self.rng.below(10) {
+            // Use a local or call a function or a local.
+            0 => {
+                let funcs: Vec<usize> = (0..self.funcs.len())
+                    .filter(|&index| &self.funcs[index].return_ty == ty)
+                    .collect();
+
+                let closures: Vec<(usize, Vec<Ty>)> = self
+                    .locals
+                    .iter()
+                    .filter_map(|(local, local_ty)| match local_ty {
+                        Ty::Func(params, return_ty) if **return_ty == *ty => {
+                            Some((*local, params.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+
+                if !funcs.is_empty() && self.rng.one_in(2) {
+                    let index = *self.rng.choose(&funcs);
+                    self.call(index, depth)
+                } else if !closures.is_empty() {
+                    let (local, params) = self.rng.choose(&closures).clone();
+                    let args: Vec<String> = params.iter().map(|ty| self.expr(ty, depth)).collect();
+                    format!("x{}({})", local, args.join(", "))
+                } else {
+                    self.leaf(ty)
+                }
+            }
+            1 => {
+                let cond = self.expr(&Ty::Bool, depth);
+                let do_branch = self.expr(ty, depth);
+                let else_branch = self.expr(ty, depth);
+                format!("(if {} do {} else {} end)", cond, do_branch, else_branch)
+            }
+            2 => {
+                let rhs_ty = self.ty(1);
+                let rhs = self.expr(&rhs_ty, depth);
+
+                let scope = self.locals.len();
+                let lhs = self.new_local(rhs_ty.clone());
+                let body = self.expr(ty, depth);
+                self.locals.truncate(scope);
+
+                format!("(let x{}: {} = {}; {})", lhs, rhs_ty, rhs, body)
+            }
+            3 => self.matching(ty, depth),
+            4 => {
+                // Project a field of a tuple that has a field of the given type.
+                let mut fields: Vec<Ty> = (0..1 + self.rng.below(2)).map(|_| self.ty(1)).collect();
+                let index = self.rng.below(fields.len() + 1);
+                fields.insert(index, ty.clone());
+
+                let tuple = self.expr(&Ty::Tuple(fields), depth);
+                format!("({}).{}", tuple, index)
+            }
+            _ => self.compound(ty, depth),
+        }
+    }
+
+    /// Generate an expression that is specific to the given type.
+    fn compound(&mut self, ty: &Ty, depth: usize) -> String {
+        match ty {
+            Ty::Int => match self.rng.below(4) {
+                0 => format!("-({})", self.expr(&Ty::Int, depth)),
+                _ => {
+                    let op = self.rng.choose(&["+", "-", "*", "/", "%", "+", "-"]);
+                    let left_op = self.expr(&Ty::Int, depth);
+                    let right_op = self.expr(&Ty::Int, depth);
+                    format!("({} {} {})", left_op, op, right_op)
+                }
+            },
+            Ty::Bool => match self.rng.below(4) {
+                0 => format!("!({})", self.expr(&Ty::Bool, depth)),
+                1 => {
+                    let op = self.rng.choose(&["&&", "||"]);
+                    let left_op = self.expr(&Ty::Bool, depth);
+                    let right_op = self.expr(&Ty::Bool, depth);
+                    format!("({} {} {})", left_op, op, right_op)
+                }
+                2 => {
+                    let op = self.rng.choose(&["==", "!="]);
+                    let operand_ty = self.ty(2);
+                    let left_op = self.expr(&operand_ty, depth);
+                    let right_op = self.expr(&operand_ty, depth);
+                    // Anonymous functions must be parenthesized to be used as operands.
+                    format!("(({}) {} ({}))", left_op, op, right_op)
+                }
+                _ => {
+                    let op = self.rng.choose(&["<", ">", "<=", ">=", "==", "!="]);
+                    let left_op = self.expr(&Ty::Int, depth);
+                    let right_op = self.expr(&Ty::Int, depth);
+                    format!("({} {} {})", left_op, op, right_op)
+                }
+            },
+            Ty::Tuple(fields) => {
+                let fields: Vec<String> = fields.iter().map(|ty| self.expr(ty, depth)).collect();
+                format!("({})", fields.join(", "))
+            }
+            Ty::Func(params, return_ty) => self.lambda(params, return_ty, depth),
+        }
+    }
+
+    /// Generate an anonymous function that might capture the locals in scope.
+    fn lambda(&mut self, params: &[Ty], return_ty: &Ty, depth: usize) -> String {
+        let scope = self.locals.len();
+        let params: Vec<String> = params
+            .iter()
+            .map(|ty| format!("x{}: {}", self.new_local(ty.clone()), ty))
+            .collect();
+        let body = self.expr(return_ty, depth);
+        self.locals.truncate(scope);
+
+        format!("fn({}): {} do {} end", params.join(", "), return_ty, body)
+    }
+
+    /// Generate a `match` expression over an integer or a tuple.
+    fn matching(&mut self, ty: &Ty, depth: usize) -> String {
+        let scope = self.locals.len();
+
+        let (scrutinee, arms) = if self.rng.one_in(2) {
+            let scrutinee = self.expr(&Ty::Int, depth);
+
+            // The literals must be different or some arms would be unreachable.
+            let mut arms = Vec::new();
+            let mut literal = self.rng.below(3);
+            for _ in 0..1 + self.rng.below(3) {
+                arms.push(format!("{} -> {}", literal, self.expr(ty, depth)));
+                literal += 1 + self.rng.below(2);
+            }
+            arms.push(format!("_ -> {}", self.expr(ty, depth)));
+
+            (scrutinee, arms)
+        } else {
+            let fields: Vec<Ty> = (0..2 + self.rng.below(2)).map(|_| self.ty(1)).collect();
+            let scrutinee = self.expr(&Ty::Tuple(fields.clone()), depth);
+
+            let patterns: Vec<String> = fields
+                .into_iter()
+                .map(|ty| {
+                    if self.rng.one_in(3) {
+                        "_".to_owned()
+                    } else {
+                        format!("x{}", self.new_local(ty))
+                    }
+                })
+                .collect();
+            let arm = format!("({}) -> {}", patterns.join(", "), self.expr(ty, depth));
+
+            (scrutinee, vec![arm])
+        };
+
+        self.locals.truncate(scope);
+
+        format!("(match {} do {} end)", scrutinee, arms.join(", "))
+    }
+
+    /// Generate an expression without nested expressions, except for the ones required to build
+    /// tuples and anonymous functions.
+    fn leaf(&mut self, ty: &Ty) -> String {
+        let locals: Vec<usize> = self
+            .locals
+            .iter()
+            .filter(|(_, local_ty)| local_ty == ty)
+            .map(|(local, _)| *local)
+            .collect();
+
+        if !locals.is_empty() && !self.rng.one_in(3) {
+            return format!("x{}", self.rng.choose(&locals));
+        }
+
+        match ty {
+            Ty::Int => match self.rng.below(50) {
+                // Large literals make overflows more likely.
+                0 => "4611686018427387904".to_owned(),
+                1 => "0".to_owned(),
+                _ => (self.rng.below(200) as i64 - 50).to_string(),
+            },
+            Ty::Bool => self.rng.choose(&["true", "false"]).to_string(),
+            Ty::Tuple(fields) => {
+                let fields: Vec<String> = fields.iter().map(|ty| self.leaf(ty)).collect();
+                format!("({})", fields.join(", "))
+            }
+            Ty::Func(params, return_ty) => {
+                // Use a function with the same type if there is one.
+                let funcs: Vec<usize> = (0..self.funcs.len())
+                    .filter(|&index| {
+                        let func = &self.funcs[index];
+                        func.params == *params && func.return_ty == **return_ty
+                    })
+                    .collect();
+
+                if funcs.is_empty() {
+                    self.lambda(params, return_ty, 0)
+                } else {
+                    format!("f{}", self.rng.choose(&funcs))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Golden tests for the programs in the `tests` directory.
+//!
+//! Every program must start with a header of `// <key>: <value>` comments. The program is
+//! compiled and run, and its output or errors are compared with the ones in the header. The keys
+//! are:
+//!
+//! - `expect`: the output of the program.
+//! - `error`: an error of the program, one comment per error. Errors are written as
+//!   `<message> at <line>:<column>`. Runtime errors are written as they are printed by the
+//!   program, like `panic: <message> at <line>:<column>`.
+//! - `flags`: the flags passed to the compiler after `run`, separated by spaces.
+//!
+//! Run `cargo test --test golden -- --bless` to update the headers with the current results.
+//! Any other argument that is not a flag is used to filter the programs by their path.
+
+mod header;
+
+use header::{Header, Outcome};
+
+use std::{
+    env::args,
+    fs::{read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+    process::{exit, Command},
+};
+
+/// The maximum number of times a program is run to update its header when blessing it.
+const BLESS_RUNS: usize = 3;
+
+fn main() {
+    let mut bless = false;
+    let mut filters = Vec::new();
+
+    // Flags passed by `cargo test` to every test target are ignored.
+    for arg in args().skip(1) {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            _ if arg.starts_with('-') => (),
+            _ => filters.push(arg),
+        }
+    }
+
+    let mut paths = Vec::new();
+    find_programs(Path::new("tests"), &mut paths);
+    paths.sort();
+
+    let mut passed = 0;
+    let mut failures = Vec::new();
+
+    for path in paths {
+        let name = path.display().to_string();
+
+        if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter.as_str())) {
+            continue;
+        }
+
+        let source = read_to_string(&path).unwrap();
+        let (mut header, program) = match Header::parse(&source) {
+            Ok(parsed) => parsed,
+            Err(reason) => {
+                println!("test {} ... FAILED", name);
+                failures.push(format!("---- {} ----\n{}\n", name, reason));
+                continue;
+            }
+        };
+
+        let found = run(&name, &header.flags);
+
+        if header.outcome.as_ref() == Some(&found) {
+            println!("test {} ... ok", name);
+            passed += 1;
+        } else if bless {
+            // The positions of the errors change if the number of comments changes, so the
+            // program is run again until its header is up to date. The header might never settle,
+            // for example if the output of the program changes between runs.
+            header.outcome = Some(found);
+            let mut runs = 1;
+
+            loop {
+                write(&path, header.write() + program).unwrap();
+
+                let found = run(&name, &header.flags);
+                if header.outcome.as_ref() == Some(&found) {
+                    println!("test {} ... blessed", name);
+                    passed += 1;
+                    break;
+                }
+
+                if runs == BLESS_RUNS {
+                    let written = header.write();
+                    header.outcome = Some(found);
+
+                    println!("test {} ... FAILED", name);
+                    failures.push(format!(
+                        "---- {} ----\nthe header did not settle\nwritten:\n{}found:\n{}",
+                        name,
+                        written,
+                        header.write()
+                    ));
+                    break;
+                }
+
+                header.outcome = Some(found);
+                runs += 1;
+            }
+        } else if header.outcome.is_none() {
+            println!("test {} ... FAILED", name);
+            failures.push(format!(
+                "---- {} ----\nthe header has no `expect` or `error` comments\n",
+                name
+            ));
+        } else {
+            let expected = header.write();
+            header.outcome = Some(found);
+
+            println!("test {} ... FAILED", name);
+            failures.push(format!(
+                "---- {} ----\nexpected:\n{}found:\n{}",
+                name,
+                expected,
+                header.write()
+            ));
+        }
+    }
+
+    for failure in &failures {
+        println!("\n{}", failure);
+    }
+
+    println!(
+        "\ntest result: {}. {} passed; {} failed",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        passed,
+        failures.len()
+    );
+
+    if !failures.is_empty() {
+        exit(1);
+    }
+}
+
+/// Find every program inside a directory and its subdirectories.
+fn find_programs(dir: &Path, paths: &mut Vec<PathBuf>) {
+    for entry in read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            find_programs(&path, paths);
+        } else if path.extension() == Some("pj".as_ref()) {
+            paths.push(path);
+        }
+    }
+}
+
+/// Compile and run a program with the given flags using the compiler's binary.
+fn run(path: &str, flags: &[String]) -> Outcome {
+    let output = Command::new(env!("CARGO_BIN_EXE_pijama"))
+        .arg("run")
+        .args(flags)
+        .arg(path)
+        .output()
+        .expect("Failed to run the compiler");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    match output.status.code() {
+        Some(0) => Outcome::Output(stdout.trim().to_owned()),
+        // The compiler failed, each diagnostic is followed by its location.
+        Some(1) => {
+            let lines: Vec<&str> = stderr.lines().collect();
+            let errors = lines
+                .windows(2)
+                .filter_map(|window| {
+                    let message = window[0].strip_prefix("error: ")?;
+                    let location = window[1].trim_start().strip_prefix("--> ")?;
+
+                    let position = location
+                        .strip_prefix(path)
+                        .and_then(|location| location.strip_prefix(':'));
+
+                    Some(match position {
+                        Some(position) => format!("{} at {}", message, position),
+                        // Diagnostics without a position only have the path.
+                        None => message.to_owned(),
+                    })
+                })
+                .collect();
+
+            Outcome::Errors(errors)
+        }
+        // The program failed at runtime.
+        Some(101) => {
+            let message = stderr.trim().replace(&format!(" at {}:", path), " at ");
+            Outcome::Errors(vec![message])
+        }
+        _ => Outcome::Errors(vec![format!("unexpected exit status: {}", output.status)]),
+    }
+}
@@ -0,0 +1,87 @@
+//! The headers of the programs in the `tests` directory.
+//!
+//! This module is shared by several test targets and not all of them use every item.
+#![allow(dead_code)]
+
+/// The result of running a program.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The program ran successfully and printed this output.
+    Output(String),
+    /// The program could not be compiled or panicked with these errors.
+    Errors(Vec<String>),
+}
+
+/// The header of a program.
+pub struct Header {
+    /// The flags passed to the compiler.
+    pub flags: Vec<String>,
+    /// The expected outcome, if the header has one.
+    pub outcome: Option<Outcome>,
+}
+
+impl Header {
+    /// Parse the header of a program.
+    ///
+    /// Returns the header and the rest of the program, or the reason why the header is invalid.
+    pub fn parse(source: &str) -> Result<(Self, &str), String> {
+        let mut flags = Vec::new();
+        let mut output = None;
+        let mut errors = Vec::new();
+        let mut len = 0;
+
+        for line in source.split_inclusive('\n') {
+            // The header ends with the first line that is not a `// <key>: <value>` comment.
+            let (key, value) = match line
+                .strip_prefix("// ")
+                .and_then(|line| line.split_once(':'))
+            {
+                Some((key, value))
+                    if !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase()) =>
+                {
+                    (key, value.trim())
+                }
+                _ => break,
+            };
+
+            match key {
+                "expect" => output = Some(value.to_owned()),
+                "error" => errors.push(value.to_owned()),
+                "flags" => flags.extend(value.split_whitespace().map(ToOwned::to_owned)),
+                _ => return Err(format!("unknown header `{}`", key)),
+            }
+
+            len += line.len();
+        }
+
+        let outcome = match (output, errors.is_empty()) {
+            (None, true) => None,
+            (Some(output), true) => Some(Outcome::Output(output)),
+            (None, false) => Some(Outcome::Errors(errors)),
+            (Some(_), false) => return Err("a program cannot have an output and errors".into()),
+        };
+
+        Ok((Self { flags, outcome }, &source[len..]))
+    }
+
+    /// Write the comments for this header.
+    pub fn write(&self) -> String {
+        let mut header = String::new();
+
+        if !self.flags.is_empty() {
+            header += &format!("// flags: {}\n", self.flags.join(" "));
+        }
+
+        match &self.outcome {
+            Some(Outcome::Output(output)) => header += &format!("// expect: {}\n", output),
+            Some(Outcome::Errors(errors)) => {
+                for error in errors {
+                    header += &format!("// error: {}\n", error);
+                }
+            }
+            None => (),
+        }
+
+        header
+    }
+}
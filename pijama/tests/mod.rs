@@ -1,17 +1,177 @@
-macro_rules! check {
-    ($name:ident) => {
-        #[test]
-        fn $name() {
-            let config = pijama_interface::Config {
-                path: concat!("tests/", stringify!($name), ".pj").into(),
-                codegen: false,
-            };
-
-            pijama_interface::Compiler::new().run(config);
-        }
+mod generator;
+mod header;
+
+use header::Header;
+use pijama_interface::{Config, Emit, Status};
+
+use std::{
+    env::var,
+    fs::{copy, read, read_dir, read_to_string, write},
+    path::Path,
+    process::Output,
+};
+
+/// The configuration used to compile a program without generating any code.
+///
+/// Tests that need a different configuration can change it using struct update syntax.
+fn config(path: &Path) -> Config {
+    Config {
+        path: path.into(),
+        codegen: false,
+        emit: Emit::Object,
+        run: false,
+        interpret: false,
+        print_signatures: false,
+        checked: true,
+    }
+}
+
+/// Run a program with the given flags using the compiler's binary, either just in time or with
+/// the interpreter.
+///
+/// The program runs in its own process because it exits when it panics.
+fn run_program(path: &str, flags: &[&str], interpret: bool) -> std::process::Output {
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_pijama"));
+    command.arg("run").args(flags);
+
+    if interpret {
+        command.arg("--interpret");
+    }
+
+    command.arg(path);
+
+    command.output().expect("Failed to run the compiler")
+}
+
+/// Run a program with the given flags with both the backend and the interpreter and check that
+/// they behave in the same way.
+fn run_differential(path: &str, flags: &[&str]) -> Output {
+    let compiled = run_program(path, flags, false);
+    let interpreted = run_program(path, flags, true);
+
+    assert_eq!(
+        String::from_utf8_lossy(&compiled.stderr),
+        String::from_utf8_lossy(&interpreted.stderr),
+        "`{}` has different errors when compiled and interpreted with {:?}",
+        path,
+        flags
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&compiled.stdout),
+        String::from_utf8_lossy(&interpreted.stdout),
+        "`{}` has different outputs when compiled and interpreted with {:?}",
+        path,
+        flags
+    );
+    assert_eq!(compiled.status.code(), interpreted.status.code());
+
+    interpreted
+}
+
+/// Compile a program into a file of the given kind and return its contents.
+fn emit_file(emit: Emit) -> Vec<u8> {
+    // The program is copied so the generated file is not written in the tests directory.
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join(format!("emit_{}", emit.extension()))
+        .with_extension("pj");
+    copy("tests/fibonacci.pj", &path).unwrap();
+
+    let config = Config {
+        codegen: true,
+        emit,
+        ..config(&path)
     };
+
+    if let Err(diagnostics) = pijama_interface::Compiler::new().run(config) {
+        panic!("{}", diagnostics);
+    }
+
+    read(path.with_extension(emit.extension())).unwrap()
 }
 
-check!(fibonacci);
-check!(apply);
-check!(arithmetic);
+#[test]
+fn emit() {
+    let ir = String::from_utf8(emit_file(Emit::Ir)).unwrap();
+    assert!(ir.contains("@entry"), "{}", ir);
+
+    let asm = String::from_utf8(emit_file(Emit::Assembly)).unwrap();
+    assert!(asm.contains("entry"), "{}", asm);
+
+    // Bitcode files start with a magic number.
+    assert!(emit_file(Emit::Bitcode).starts_with(b"BC"));
+}
+
+#[test]
+fn runtime_errors() {
+    let path = Path::new("tests/division_by_zero.pj");
+
+    // Both run modes return runtime errors instead of exiting the process.
+    for interpret in [false, true] {
+        let config = Config {
+            codegen: true,
+            run: true,
+            interpret,
+            ..config(path)
+        };
+
+        match pijama_interface::Compiler::new().run(config) {
+            Ok(Status::Failed(error)) => assert_eq!(
+                error,
+                format!("panic: attempt to divide by zero at {}:3:5", path.display())
+            ),
+            Ok(status) => panic!("unexpected status {:?}", status),
+            Err(diagnostics) => panic!("{}", diagnostics),
+        }
+    }
+}
+
+/// The programs that are not run by the differential tests.
+///
+/// The backend does not limit the depth of the calls so it would overflow the stack instead of
+/// failing gracefully.
+const INTERPRETER_ONLY: &[&str] = &["max_depth.pj"];
+
+#[test]
+fn differential() {
+    let mut paths: Vec<String> = read_dir("tests")
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".pj") && !INTERPRETER_ONLY.contains(&name.as_str()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let path = format!("tests/{}", path);
+        // Programs are run with the flags of their header, so each mode is compared.
+        let source = read_to_string(&path).unwrap();
+        let (header, _) = Header::parse(&source).unwrap();
+        let flags: Vec<&str> = header.flags.iter().map(String::as_str).collect();
+
+        run_differential(&path, &flags);
+    }
+}
+
+#[test]
+fn differential_random() {
+    // The number of programs can be increased to look for miscompilations.
+    let count = var("PIJAMA_RANDOM_PROGRAMS").map_or(64, |count| count.parse().unwrap());
+
+    for seed in 0..count {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("random_{}.pj", seed));
+        write(&path, generator::program(seed)).unwrap();
+
+        // Overflows and divisions are handled differently in release mode, so both modes are
+        // compared.
+        for flags in [&[][..], &["--release"][..]] {
+            let output = run_differential(path.to_str().unwrap(), flags);
+
+            // Generated programs must always compile.
+            assert!(
+                matches!(output.status.code(), Some(0) | Some(101)),
+                "`{}` does not compile:\n{}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+}